@@ -0,0 +1,437 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Base65536 over a custom set of blocks.
+//!
+//! Base65536 maps the first octet of every pair to a character within a block
+//! of 256 characters, and the second octet to which of 256 blocks that is. A
+//! lone final octet uses a separate padding block instead. An [`Alphabet`]
+//! replaces the standard blocks with any others, such as to avoid characters
+//! missing from a font, and an [`Engine`] encodes and decodes with it.
+//!
+//! Data encoded with anything but the standard alphabet can only be decoded
+//! with the same alphabet.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::alphabet::{Alphabet, Engine};
+//!
+//! // use Hangul syllables instead of the blocks of Linear A, Cuneiform,
+//! // Egyptian and Anatolian hieroglyphs, and Bamum
+//! let standard = Alphabet::default();
+//! let mut hangul = (0xAC..).map(|block| block << 8);
+//! let block_starts: Vec<u32> = standard
+//!     .block_starts()
+//!     .iter()
+//!     .map(|&start| match start {
+//!         0x10000..=0x1FFFF => hangul.next().unwrap(),
+//!         _ => start,
+//!     })
+//!     .collect();
+//!
+//! let engine = Engine::new(Alphabet::new(&block_starts, standard.padding_block_start())?);
+//! let encoded = engine.encode("hello world", None);
+//! assert_eq!("驨ꍬ啯굷ꍲᕤ", encoded);
+//! assert_eq!(b"hello world", &engine.decode(&encoded, false)?[..]);
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+//!
+//! [`Alphabet`]: struct.Alphabet.html
+//! [`Engine`]: struct.Engine.html
+
+use crate::width::display_width;
+use crate::{
+    inner_decode_with, write_lines, DecodeResult, Hasher, WrapOptions, BLOCK_STARTS,
+    PADDING_BLOCK_START,
+};
+use std::collections::HashMap;
+use std::{error, fmt};
+
+const BLOCK_SIZE: u32 = 1 << 8;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+/// A validated set of 256 blocks, and a padding block, to encode with.
+///
+/// Used with [`Engine`]. See it for examples.
+///
+/// [`Engine`]: struct.Engine.html
+pub struct Alphabet {
+    block_starts: Vec<u32>,
+    padding_block_start: u32,
+    block_start_to_index: HashMap<u32, u8, Hasher>,
+}
+
+impl Alphabet {
+    /// Create an alphabet from the first code point of each of 256 blocks,
+    /// in the order of the octets they stand for, and of the padding block.
+    ///
+    /// Every block must be made up of valid characters, but which characters
+    /// is otherwise left to the caller. For the output to survive being
+    /// copied and pasted, the blocks should only have characters which are
+    /// assigned, and which aren't whitespace, control, combining, or changed
+    /// by normalization, as the standard blocks are.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AlphabetError::WrongBlockCount`] if there aren't exactly 256
+    /// block starts, [`AlphabetError::Misaligned`] if a block doesn't start at
+    /// a multiple of 256, [`AlphabetError::InvalidBlock`] if a block has
+    /// surrogates or code points beyond U+10FFFF, and
+    /// [`AlphabetError::Duplicate`] if any block, including the padding block,
+    /// is used twice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base65536::alphabet::{Alphabet, AlphabetError};
+    ///
+    /// let block_starts: Vec<u32> = (0x100..0x200).map(|block| block << 8).collect();
+    /// assert!(Alphabet::new(&block_starts, 0x1500).is_ok());
+    ///
+    /// assert_eq!(
+    ///     Err(AlphabetError::WrongBlockCount(255)),
+    ///     Alphabet::new(&block_starts[1..], 0x1500)
+    /// );
+    /// assert_eq!(Err(AlphabetError::Misaligned(0x1580)), Alphabet::new(&block_starts, 0x1580));
+    /// assert_eq!(Err(AlphabetError::InvalidBlock(0xD800)), Alphabet::new(&block_starts, 0xD800));
+    /// assert_eq!(Err(AlphabetError::Duplicate(0x10000)), Alphabet::new(&block_starts, 0x10000));
+    /// ```
+    ///
+    /// [`AlphabetError::WrongBlockCount`]: enum.AlphabetError.html#variant.WrongBlockCount
+    /// [`AlphabetError::Misaligned`]: enum.AlphabetError.html#variant.Misaligned
+    /// [`AlphabetError::InvalidBlock`]: enum.AlphabetError.html#variant.InvalidBlock
+    /// [`AlphabetError::Duplicate`]: enum.AlphabetError.html#variant.Duplicate
+    pub fn new(block_starts: &[u32], padding_block_start: u32) -> Result<Self, AlphabetError> {
+        if block_starts.len() != 256 {
+            return Err(AlphabetError::WrongBlockCount(block_starts.len()));
+        }
+
+        let mut block_start_to_index = HashMap::with_capacity_and_hasher(256, Hasher::default());
+        for (index, &start) in block_starts.iter().enumerate() {
+            validate_block(start)?;
+            if block_start_to_index.insert(start, index as u8).is_some() {
+                return Err(AlphabetError::Duplicate(start));
+            }
+        }
+        validate_block(padding_block_start)?;
+        if block_start_to_index.contains_key(&padding_block_start) {
+            return Err(AlphabetError::Duplicate(padding_block_start));
+        }
+
+        Ok(Alphabet {
+            block_starts: block_starts.to_vec(),
+            padding_block_start,
+            block_start_to_index,
+        })
+    }
+
+    /// The first code point of each block, in the order of the octets they
+    /// stand for.
+    pub fn block_starts(&self) -> &[u32] {
+        &self.block_starts
+    }
+
+    /// The first code point of the padding block.
+    pub fn padding_block_start(&self) -> u32 {
+        self.padding_block_start
+    }
+
+    #[inline]
+    fn decode_code_point(&self, code_point: char) -> Option<(u8, Option<u8>)> {
+        let code_point = code_point as u32;
+        let byte1 = code_point % BLOCK_SIZE;
+        let block_start = code_point - byte1;
+
+        if block_start == self.padding_block_start {
+            Some((byte1 as u8, None))
+        } else {
+            self.block_start_to_index
+                .get(&block_start)
+                .map(|&byte2| (byte1 as u8, Some(byte2)))
+        }
+    }
+
+    #[inline]
+    fn encode_code_point(&self, byte1: u8, byte2: Option<u8>) -> char {
+        let block_start = match byte2 {
+            None => self.padding_block_start,
+            Some(byte2) => self.block_starts[usize::from(byte2)],
+        };
+
+        // It is safe to unwrap because every block was checked to be made up
+        // of valid characters.
+        std::char::from_u32(block_start + u32::from(byte1)).unwrap()
+    }
+}
+
+impl Default for Alphabet {
+    /// The standard base65536 alphabet, as used by the crate root.
+    fn default() -> Self {
+        // It is safe to unwrap because the standard blocks are valid.
+        Alphabet::new(BLOCK_STARTS, PADDING_BLOCK_START).unwrap()
+    }
+}
+
+fn validate_block(start: u32) -> Result<(), AlphabetError> {
+    if start % BLOCK_SIZE != 0 {
+        Err(AlphabetError::Misaligned(start))
+    } else if (0xD800..0xE000).contains(&start) || start > 0x10FFFF - (BLOCK_SIZE - 1) {
+        Err(AlphabetError::InvalidBlock(start))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an invalid set of blocks.
+///
+/// Returned by [`Alphabet::new`].
+///
+/// [`Alphabet::new`]: struct.Alphabet.html#method.new
+pub enum AlphabetError {
+    /// There weren't 256 block starts.
+    ///
+    /// Contains how many there were.
+    WrongBlockCount(usize),
+    /// A block didn't start at a multiple of 256.
+    ///
+    /// Contains the start of the block.
+    Misaligned(u32),
+    /// A block contained surrogates, or code points beyond U+10FFFF.
+    ///
+    /// Contains the start of the block.
+    InvalidBlock(u32),
+    /// A block was used more than once.
+    ///
+    /// Contains the start of the block.
+    Duplicate(u32),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AlphabetError::WrongBlockCount(count) => {
+                write!(f, "expected 256 block starts, found {}", count)
+            }
+            AlphabetError::Misaligned(start) => {
+                write!(f, "block at U+{:04X} isn't aligned to 256", start)
+            }
+            AlphabetError::InvalidBlock(start) => {
+                write!(f, "block at U+{:04X} has invalid characters", start)
+            }
+            AlphabetError::Duplicate(start) => {
+                write!(f, "block at U+{:04X} is used more than once", start)
+            }
+        }
+    }
+}
+
+impl error::Error for AlphabetError {
+    fn description(&self) -> &str {
+        match *self {
+            AlphabetError::WrongBlockCount(_) => "wrong number of blocks",
+            AlphabetError::Misaligned(_) => "misaligned block",
+            AlphabetError::InvalidBlock(_) => "invalid block",
+            AlphabetError::Duplicate(_) => "duplicate block",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// Encodes and decodes base65536 over an [`Alphabet`].
+///
+/// The methods mirror the functions of the crate root, and behave in exactly
+/// the same way with the default alphabet.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::alphabet::{Alphabet, Engine};
+///
+/// let block_starts: Vec<u32> = (0x100..0x200).map(|block| block << 8).collect();
+/// let engine = Engine::new(Alphabet::new(&block_starts, 0x1500)?);
+///
+/// let encoded = engine.encode(&[1, 2, 3], None);
+/// assert_eq!("\u{10201}ᔃ", encoded);
+/// assert_eq!(vec![1, 2, 3], engine.decode(&encoded, false)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Alphabet`]: struct.Alphabet.html
+pub struct Engine {
+    alphabet: Alphabet,
+}
+
+impl Engine {
+    /// Create an engine encoding and decoding with an alphabet.
+    pub fn new(alphabet: Alphabet) -> Self {
+        Engine { alphabet }
+    }
+
+    /// The alphabet being encoded and decoded with.
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    #[inline]
+    fn inner_decode<F>(&self, input: &str, ignore_garbage: bool, out: F) -> DecodeResult<()>
+    where
+        F: FnMut(u8, Option<u8>),
+    {
+        inner_decode_with(
+            input,
+            ignore_garbage,
+            |code_point| self.alphabet.decode_code_point(code_point),
+            out,
+            |_, _| {},
+        )
+    }
+
+    /// Decode from a reference to a string encoded with this alphabet as
+    /// octets.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`decode`] in the crate root, but going by the blocks of
+    /// this alphabet.
+    ///
+    /// [`decode`]: ../fn.decode.html
+    pub fn decode<T>(&self, input: &T, ignore_garbage: bool) -> DecodeResult<Vec<u8>>
+    where
+        T: ?Sized + AsRef<str>,
+    {
+        let mut buf = Vec::with_capacity(input.as_ref().len());
+        self.decode_buf(input, &mut buf, ignore_garbage)
+            .map(|_| buf)
+    }
+
+    /// Decode from a reference to a string encoded with this alphabet as
+    /// octets. Writes into the supplied output buffer, growing it if needed.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`decode`] in the crate root, but going by the blocks of
+    /// this alphabet.
+    ///
+    /// [`decode`]: ../fn.decode.html
+    pub fn decode_buf<T>(
+        &self,
+        input: &T,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> DecodeResult<()>
+    where
+        T: ?Sized + AsRef<str>,
+    {
+        self.inner_decode(input.as_ref(), ignore_garbage, |a, b| {
+            buf.push(a);
+            if let Some(b) = b {
+                buf.push(b)
+            }
+        })
+    }
+
+    /// Decode from a reference to a string encoded with this alphabet as
+    /// octets. Writes into the supplied slice, returning how many bytes were
+    /// written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is not long enough.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`decode`] in the crate root, but going by the blocks of
+    /// this alphabet.
+    ///
+    /// [`decode`]: ../fn.decode.html
+    pub fn decode_slice<T>(
+        &self,
+        input: &T,
+        buf: &mut [u8],
+        ignore_garbage: bool,
+    ) -> DecodeResult<usize>
+    where
+        T: ?Sized + AsRef<str>,
+    {
+        let mut pos = 0;
+        self.inner_decode(input.as_ref(), ignore_garbage, |a, b| {
+            buf[pos] = a;
+            pos += 1;
+            if let Some(b) = b {
+                buf[pos] = b;
+                pos += 1;
+            }
+        })
+        .map(|_| pos)
+    }
+
+    /// Encode arbitrary octets with this alphabet.
+    ///
+    /// The `wrap` option allows wrapping the output every so many characters,
+    /// optionally with a supplied string using [`WrapOptions`]. The characters
+    /// of any end-of-line string or prefix shouldn't be in this alphabet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if set to wrap every 0 columns.
+    ///
+    /// [`WrapOptions`]: ../enum.WrapOptions.html
+    pub fn encode<'a, T, W>(&self, input: &T, wrap: W) -> String
+    where
+        T: ?Sized + AsRef<[u8]>,
+        W: Into<WrapOptions<'a>>,
+    {
+        // Every two bytes of input becomes a character of up to four bytes.
+        // This allocates for the unwrapped output.
+        let mut output = String::with_capacity(input.as_ref().len() * 2);
+        self.encode_buf(input, &mut output, wrap);
+        output
+    }
+
+    /// Encode arbitrary octets with this alphabet. Writes into the supplied
+    /// output buffer, growing it if needed.
+    ///
+    /// The `wrap` option allows wrapping the output every so many characters,
+    /// optionally with a supplied string using [`WrapOptions`]. The characters
+    /// of any end-of-line string or prefix shouldn't be in this alphabet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if set to wrap every 0 columns.
+    ///
+    /// [`WrapOptions`]: ../enum.WrapOptions.html
+    pub fn encode_buf<'a, T, W>(&self, input: &T, buf: &mut String, wrap: W)
+    where
+        T: ?Sized + AsRef<[u8]>,
+        W: Into<WrapOptions<'a>>,
+    {
+        let format = wrap.into().line_format();
+        let chars = input.as_ref().chunks(2).map(|bytes| {
+            let code_point = self
+                .alphabet
+                .encode_code_point(bytes[0], bytes.get(1).cloned());
+            (
+                code_point,
+                format.measure.char_width(code_point, display_width),
+            )
+        });
+        write_lines(chars, buf, format);
+    }
+}
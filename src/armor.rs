@@ -0,0 +1,342 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A PEM-style armor around base65536, for pasting into tickets and chat.
+//!
+//! An armored block looks like this:
+//!
+//! ```text
+//! ----- BEGIN BASE65536 GREETING -----
+//! Comment: an example
+//!
+//! 驨ꍬ啯
+//! 𒁷ꍲᕤ
+//! =0D4A1185
+//! ----- END BASE65536 GREETING -----
+//! ```
+//!
+//! The block starts with a line naming its label, and is followed by any
+//! number of `Key: value` header lines, which end with a blank line if there
+//! are any. The wrapped base65536 body follows, and then a line giving the
+//! CRC-32 of the data, and finally a line closing the label.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::armor::{armor, dearmor};
+//!
+//! let armored = armor("hello world", "GREETING", &[("Comment", "an example")], 3)?;
+//! let message = format!("Here it is:\n\n{}\nThanks!", armored);
+//!
+//! let block = dearmor(&message)?;
+//! assert_eq!("GREETING", block.label);
+//! assert_eq!(vec![("Comment".to_owned(), "an example".to_owned())], block.headers);
+//! assert_eq!(b"hello world", &block.data[..]);
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+
+use crate::{decode_buf, encode_buf, Error, WrapOptions};
+use lazy_static::lazy_static;
+use std::{error, fmt};
+
+const BEGIN: &str = "----- BEGIN BASE65536 ";
+const END: &str = "----- END BASE65536 ";
+const DASHES: &str = " -----";
+
+lazy_static! {
+    static ref CRC_TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        for (byte, entry) in table.iter_mut().enumerate() {
+            *entry = (0..8).fold(byte as u32, |crc, _| {
+                if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                }
+            });
+        }
+        table
+    };
+}
+
+/// Calculates the CRC-32 (as used by zlib, PNG, etc.) of some octets.
+pub(crate) fn crc32(input: &[u8]) -> u32 {
+    !input.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while armoring or dearmoring.
+///
+/// Used with [`armor`] and [`dearmor`]. See them for examples.
+///
+/// [`armor`]: fn.armor.html
+/// [`dearmor`]: fn.dearmor.html
+pub enum ArmorError {
+    /// The label was empty, had leading or trailing spaces, or contained a
+    /// line break or a run of five dashes.
+    InvalidLabel,
+    /// A header had an empty key, a key containing a colon, a key or value
+    /// with leading or trailing whitespace, or contained a line break.
+    InvalidHeader,
+    /// No line beginning an armored block was found.
+    MissingBegin,
+    /// No line giving the checksum was found.
+    MissingChecksum,
+    /// No line ending the armored block was found.
+    MissingEnd,
+    /// The body of the armored block was not valid base65536.
+    Decode(Error),
+    /// The checksum of the data didn't match the one in the armored block.
+    ///
+    /// Contains the checksum given in the armored block, and the actual
+    /// checksum of the data.
+    ChecksumMismatch(u32, u32),
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArmorError::InvalidLabel => write!(f, "invalid label"),
+            ArmorError::InvalidHeader => write!(f, "invalid header"),
+            ArmorError::MissingBegin => write!(f, "no armored block found"),
+            ArmorError::MissingChecksum => write!(f, "armored block has no checksum"),
+            ArmorError::MissingEnd => write!(f, "armored block is not terminated"),
+            ArmorError::Decode(ref err) => write!(f, "{}", err),
+            ArmorError::ChecksumMismatch(expected, actual) => write!(
+                f,
+                "checksum mismatch: expected {:08X}, found {:08X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl error::Error for ArmorError {
+    fn description(&self) -> &str {
+        match *self {
+            ArmorError::InvalidLabel => "invalid label",
+            ArmorError::InvalidHeader => "invalid header",
+            ArmorError::MissingBegin => "missing begin line",
+            ArmorError::MissingChecksum => "missing checksum",
+            ArmorError::MissingEnd => "missing end line",
+            ArmorError::Decode(_) => "invalid base65536",
+            ArmorError::ChecksumMismatch(_, _) => "checksum mismatch",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ArmorError::Decode(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+/// The contents of an armored block.
+///
+/// Returned by [`dearmor`]. See it for examples.
+///
+/// [`dearmor`]: fn.dearmor.html
+pub struct Armored {
+    /// The label naming what the block contains.
+    pub label: String,
+    /// Every header, as keys and values, in order.
+    pub headers: Vec<(String, String)>,
+    /// The decoded data.
+    pub data: Vec<u8>,
+}
+
+fn valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.trim() == label
+        && !label.contains("-----")
+        && !label.chars().any(char::is_control)
+}
+
+fn valid_header(key: &str, value: &str) -> bool {
+    !key.is_empty()
+        && key.trim() == key
+        && value.trim() == value
+        && !key.contains(':')
+        && !key.chars().chain(value.chars()).any(char::is_control)
+}
+
+/// Encode arbitrary octets as an armored block of base65536.
+///
+/// The body is wrapped according to `wrap`. For the block to be read back by
+/// [`dearmor`], lines must only be separated with line breaks, and must not
+/// have a line prefix. The block ends with a line break.
+///
+/// # Errors
+///
+/// Returns [`ArmorError::InvalidLabel`] or [`ArmorError::InvalidHeader`] if
+/// the label or a header couldn't be read back.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// [`dearmor`]: fn.dearmor.html
+/// [`ArmorError::InvalidLabel`]: enum.ArmorError.html#variant.InvalidLabel
+/// [`ArmorError::InvalidHeader`]: enum.ArmorError.html#variant.InvalidHeader
+pub fn armor<'a, T, W>(
+    input: &T,
+    label: &str,
+    headers: &[(&str, &str)],
+    wrap: W,
+) -> Result<String, ArmorError>
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    let input = input.as_ref();
+    if !valid_label(label) {
+        return Err(ArmorError::InvalidLabel);
+    } else if !headers.iter().all(|&(key, value)| valid_header(key, value)) {
+        return Err(ArmorError::InvalidHeader);
+    }
+
+    let mut output = String::with_capacity(input.len() * 2 + 128);
+    output.push_str(BEGIN);
+    output.push_str(label);
+    output.push_str(DASHES);
+    output.push('\n');
+
+    for &(key, value) in headers {
+        output.push_str(key);
+        output.push_str(": ");
+        output.push_str(value);
+        output.push('\n');
+    }
+    if !headers.is_empty() {
+        output.push('\n');
+    }
+
+    encode_buf(input, &mut output, wrap);
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+
+    output.push_str(&format!("={:08X}\n", crc32(input)));
+    output.push_str(END);
+    output.push_str(label);
+    output.push_str(DASHES);
+    output.push('\n');
+    Ok(output)
+}
+
+/// Finds and decodes the first armored block of base65536 within some text.
+///
+/// Any text before and after the block is ignored, as is whitespace at the
+/// end of each line of the block.
+///
+/// # Errors
+///
+/// Returns [`ArmorError::MissingBegin`], [`ArmorError::MissingChecksum`] or
+/// [`ArmorError::MissingEnd`] if no complete block could be found,
+/// [`ArmorError::InvalidHeader`] if a header couldn't be read, and
+/// [`ArmorError::Decode`] if the body isn't valid base65536. Offsets within
+/// the decoding error are into the body with its line breaks removed.
+///
+/// If the block is read, but the data doesn't match its checksum,
+/// [`ArmorError::ChecksumMismatch`] is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::armor::{dearmor, ArmorError};
+///
+/// let armored = "----- BEGIN BASE65536 GREETING -----\n\
+///                驨ꍬ啯𒁷ꍲᕤ\n\
+///                =00000000\n\
+///                ----- END BASE65536 GREETING -----";
+/// assert_eq!(
+///     Err(ArmorError::ChecksumMismatch(0, 0x0D4A1185)),
+///     dearmor(armored)
+/// );
+/// ```
+///
+/// [`ArmorError::MissingBegin`]: enum.ArmorError.html#variant.MissingBegin
+/// [`ArmorError::MissingChecksum`]: enum.ArmorError.html#variant.MissingChecksum
+/// [`ArmorError::MissingEnd`]: enum.ArmorError.html#variant.MissingEnd
+/// [`ArmorError::InvalidHeader`]: enum.ArmorError.html#variant.InvalidHeader
+/// [`ArmorError::Decode`]: enum.ArmorError.html#variant.Decode
+/// [`ArmorError::ChecksumMismatch`]: enum.ArmorError.html#variant.ChecksumMismatch
+pub fn dearmor(input: &str) -> Result<Armored, ArmorError> {
+    let mut lines = input.lines().map(str::trim_end);
+
+    let label = lines
+        .by_ref()
+        .filter_map(|line| line.strip_prefix(BEGIN)?.strip_suffix(DASHES))
+        .find(|label| valid_label(label))
+        .ok_or(ArmorError::MissingBegin)?;
+
+    let mut block = Armored {
+        label: label.to_owned(),
+        ..Armored::default()
+    };
+
+    let mut line = lines.next().ok_or(ArmorError::MissingChecksum)?;
+    if line.contains(':') {
+        while !line.is_empty() {
+            let (key, value) = line.split_once(':').ok_or(ArmorError::InvalidHeader)?;
+            let value = value.trim_start();
+            if !valid_header(key, value) {
+                return Err(ArmorError::InvalidHeader);
+            }
+
+            block.headers.push((key.to_owned(), value.to_owned()));
+            line = lines.next().ok_or(ArmorError::MissingChecksum)?;
+        }
+        line = lines.next().ok_or(ArmorError::MissingChecksum)?;
+    }
+
+    let mut body = String::new();
+    let checksum = loop {
+        if let Some(checksum) = line.strip_prefix('=') {
+            break checksum;
+        } else if line.starts_with(END) {
+            return Err(ArmorError::MissingChecksum);
+        }
+
+        body.push_str(line);
+        line = lines.next().ok_or(ArmorError::MissingChecksum)?;
+    };
+    let expected = match u32::from_str_radix(checksum, 16) {
+        Ok(expected) if checksum.len() == 8 => expected,
+        _ => return Err(ArmorError::MissingChecksum),
+    };
+
+    let end = lines.next().ok_or(ArmorError::MissingEnd)?;
+    if end
+        .strip_prefix(END)
+        .and_then(|end| end.strip_suffix(DASHES))
+        != Some(label)
+    {
+        return Err(ArmorError::MissingEnd);
+    }
+
+    decode_buf(&body, &mut block.data, false).map_err(ArmorError::Decode)?;
+    let actual = crc32(&block.data);
+    if expected == actual {
+        Ok(block)
+    } else {
+        Err(ArmorError::ChecksumMismatch(expected, actual))
+    }
+}
@@ -0,0 +1,393 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An implementation of [fumiama]'s [base16384], for exchanging data with
+//! tools that use it.
+//!
+//! Every base16384 character is one of the 16,384 CJK ideographs from U+4E00
+//! to U+8DFF, and carries 14 bits, so every 7 octets become 4 characters.
+//! If the length of the data isn't a multiple of 7, the final group is padded
+//! with zero bits, and followed by a character from U+3D01 to U+3D06 saying
+//! how many octets that group holds.
+//!
+//! Files written by the reference implementation are UTF-16 and usually begin
+//! with a byte order mark. Encoding never writes one, but decoding skips one
+//! at the beginning of the input.
+//!
+//! When wrapping with [`WrapOptions`], the tail marker counts as a character
+//! like any other, so it may be left alone on the final line.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::base16384::{decode, encode};
+//!
+//! let encoded = encode("hello world", None);
+//! assert_eq!("栙擆羼湷槜瓆帀㴄", encoded);
+//! assert_eq!(b"hello world", &decode(&encoded, false)?[..]);
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+//!
+//! [fumiama]: https://github.com/fumiama
+//! [base16384]: https://github.com/fumiama/base16384
+//! [`WrapOptions`]: ../enum.WrapOptions.html
+
+pub use crate::WrapOptions;
+
+use crate::write_lines;
+use std::{error, fmt};
+
+// The first character carrying 14 bits; the rest follow it.
+const START: u32 = 0x4E00;
+// The characters ending a short final group, less the octets it holds.
+const TAIL_START: u32 = 0x3D00;
+const BOM: char = '\u{FEFF}';
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while decoding.
+///
+/// Used with [`decode`], [`decode_buf`] and [`decode_slice`]. See them for
+/// examples.
+///
+/// [`decode`]: fn.decode.html
+/// [`decode_buf`]: fn.decode_buf.html
+/// [`decode_slice`]: fn.decode_slice.html
+pub enum Error {
+    /// A code point not valid in base16384 was found in the input stream.
+    /// Consider using the `ignore_garbage` option.
+    ///
+    /// Contains the offset from the beginning of the stream at which the
+    /// invalid code point was found, and the actual code point.
+    InvalidCodePoint(usize, char),
+    /// The base16384 stream continued after a tail marker, the final group
+    /// didn't have as many characters as its tail marker says it should, or
+    /// a short final group had no tail marker.
+    InvalidLength,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidCodePoint(offset, ch) => {
+                write!(f, "invalid code point '{}' at offset {}", ch, offset)
+            }
+            Error::InvalidLength => write!(f, "final group doesn't match its tail marker"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::InvalidCodePoint(_, _) => "invalid code point",
+            Error::InvalidLength => "invalid length",
+        }
+    }
+}
+
+/// A specialized [`Result`] type for decoding operations.
+///
+/// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
+pub type DecodeResult<T> = ::std::result::Result<T, Error>;
+
+// The number of characters needed to carry a group of octets.
+#[inline]
+fn group_chars(octets: usize) -> usize {
+    (octets * 8).div_ceil(14)
+}
+
+// What a character carries: either 14 bits, or the number of octets in the
+// final group.
+enum Symbol {
+    Bits(u64),
+    Tail(usize),
+}
+
+#[inline]
+fn decode_code_point(code_point: char) -> Option<Symbol> {
+    match code_point as u32 {
+        value @ 0x4E00..=0x8DFF => Some(Symbol::Bits(u64::from(value - START))),
+        value @ 0x3D01..=0x3D06 => Some(Symbol::Tail((value - TAIL_START) as usize)),
+        _ => None,
+    }
+}
+
+// Writes the first `octets` octets of a group, which is left-aligned in the
+// low 56 bits.
+#[inline]
+fn write_group<F>(group: u64, octets: usize, out: &mut F)
+where
+    F: FnMut(u8),
+{
+    for index in 0..octets {
+        out((group >> (48 - index * 8)) as u8);
+    }
+}
+
+#[inline]
+fn inner_decode<F>(input: &str, ignore_garbage: bool, mut out: F) -> DecodeResult<()>
+where
+    F: FnMut(u8),
+{
+    let skip = if input.starts_with(BOM) {
+        BOM.len_utf8()
+    } else {
+        0
+    };
+
+    // A complete group isn't written until the next character is seen, as a
+    // tail marker may yet say it only holds six octets.
+    let mut complete = None;
+    let (mut group, mut len) = (0u64, 0);
+    let mut ended = false;
+
+    for (index, code_point) in input[skip..].char_indices() {
+        let symbol = match decode_code_point(code_point) {
+            Some(_) if ended => return Err(Error::InvalidLength),
+            Some(symbol) => symbol,
+            None if ignore_garbage => continue,
+            None => return Err(Error::InvalidCodePoint(skip + index, code_point)),
+        };
+
+        match symbol {
+            Symbol::Bits(bits) => {
+                if let Some(complete) = complete.take() {
+                    write_group(complete, 7, &mut out);
+                }
+                group |= bits << (42 - len * 14);
+                len += 1;
+                if len == 4 {
+                    complete = Some(group);
+                    group = 0;
+                    len = 0;
+                }
+            }
+            Symbol::Tail(octets) => {
+                match complete.take() {
+                    Some(complete) if len == 0 && octets == 6 => {
+                        write_group(complete, octets, &mut out)
+                    }
+                    None if len > 0 && len == group_chars(octets) => {
+                        write_group(group, octets, &mut out)
+                    }
+                    _ => return Err(Error::InvalidLength),
+                }
+                len = 0;
+                ended = true;
+            }
+        }
+    }
+
+    if len > 0 {
+        Err(Error::InvalidLength)
+    } else {
+        if let Some(complete) = complete {
+            write_group(complete, 7, &mut out);
+        }
+        Ok(())
+    }
+}
+
+/// Decode from a reference to a base16384-encoded string as octets.
+///
+/// A byte order mark at the beginning of the input is skipped.
+///
+/// # Errors
+///
+/// If the input string contains a character that isn't base16384,
+/// [`Error::InvalidCodePoint`] will be returned, along with the bad character,
+/// and its position in the input. As with base65536, this is *very* strict
+/// by default, even failing on line breaks. To prevent this, use with the
+/// `ignore_garbage` option.
+///
+/// If the final group of characters doesn't match its tail marker, has no
+/// tail marker when it needs one, or the stream continues after the tail
+/// marker, [`Error::InvalidLength`] is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::base16384::decode;
+///
+/// assert_eq!(vec![1, 2, 3], decode("乀渰㴃", false)?);
+/// assert_eq!("hello world", String::from_utf8(decode("栙擆羼湷槜瓆帀㴄", false)?)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+/// [`Error::InvalidLength`]: enum.Error.html#variant.InvalidLength
+pub fn decode<T>(input: &T, ignore_garbage: bool) -> DecodeResult<Vec<u8>>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let mut buf = Vec::with_capacity(input.as_ref().len());
+    decode_buf(input, &mut buf, ignore_garbage).map(|_| buf)
+}
+
+/// Decode from a reference to a base16384-encoded string as octets.
+/// Writes into the supplied output buffer, growing it if needed.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::base16384::decode_buf;
+///
+/// let mut buf = Vec::new();
+/// decode_buf("栙擆羼湷槜瓆帀㴄", &mut buf, false)?;
+/// assert_eq!("hello world", String::from_utf8(buf)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: fn.decode.html
+pub fn decode_buf<T>(input: &T, buf: &mut Vec<u8>, ignore_garbage: bool) -> DecodeResult<()>
+where
+    T: ?Sized + AsRef<str>,
+{
+    inner_decode(input.as_ref(), ignore_garbage, |byte| buf.push(byte))
+}
+
+/// Decode from a reference to a base16384-encoded string as octets.
+/// Writes into the supplied slice, returning how many bytes were written.
+///
+/// # Panics
+///
+/// Panics if the slice is not long enough.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::base16384::decode_slice;
+///
+/// let mut buf = [0; 11];
+/// decode_slice("栙擆羼湷槜瓆帀㴄", &mut buf, false)?;
+/// assert_eq!(b"hello world", &buf);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: fn.decode.html
+pub fn decode_slice<T>(input: &T, buf: &mut [u8], ignore_garbage: bool) -> DecodeResult<usize>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let mut pos = 0;
+    inner_decode(input.as_ref(), ignore_garbage, |byte| {
+        buf[pos] = byte;
+        pos += 1;
+    })
+    .map(|_| pos)
+}
+
+/// Encode arbitrary octets as base16384.
+///
+/// The `wrap` option allows wrapping the output every so many characters,
+/// optionally with a supplied string using [`WrapOptions`]. The characters
+/// of any end-of-line string or prefix shouldn't be base16384.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::base16384::{encode, WrapOptions};
+///
+/// assert_eq!("栙擆羼湷槜瓆帀㴄", encode("hello world", None));
+/// assert_eq!("栙擆羼湷\r\n槜瓆帀㴄", encode("hello world", WrapOptions::WrapAtWith(4, "\r\n")));
+/// ```
+///
+/// [`WrapOptions`]: ../enum.WrapOptions.html
+pub fn encode<'a, T, W>(input: &T, wrap: W) -> String
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    // Every 7 bytes of input becomes 4 characters of three bytes each, and a
+    // short final group takes at most 5. This allocates for the unwrapped
+    // output.
+    let mut output = String::with_capacity((input.as_ref().len() / 7 * 4 + 5) * 3);
+    encode_buf(input, &mut output, wrap);
+    output
+}
+
+/// Encode arbitrary octets as base16384. Writes into the supplied output
+/// buffer, growing it if needed.
+///
+/// The `wrap` option allows wrapping the output every so many characters,
+/// optionally with a supplied string using [`WrapOptions`]. The characters
+/// of any end-of-line string or prefix shouldn't be base16384.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::base16384::encode_buf;
+///
+/// let mut buf = String::new();
+/// encode_buf("hello world", &mut buf, 4);
+///
+/// assert_eq!("栙擆羼湷\n槜瓆帀㴄", buf);
+/// ```
+///
+/// [`WrapOptions`]: ../enum.WrapOptions.html
+pub fn encode_buf<'a, T, W>(input: &T, buf: &mut String, wrap: W)
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    let format = wrap.into().line_format();
+    let chars = input.as_ref().chunks(7).flat_map(|chunk| {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u64, |group, (index, &byte)| {
+                group | u64::from(byte) << (48 - index * 8)
+            });
+        let tail = if chunk.len() < 7 {
+            Some(TAIL_START + chunk.len() as u32)
+        } else {
+            None
+        };
+
+        // It is safe to unwrap because every value is a CJK ideograph, or a
+        // tail marker from CJK Extension A.
+        (0..group_chars(chunk.len()))
+            .map(move |index| START + ((group >> (42 - index * 14)) & 0x3FFF) as u32)
+            .chain(tail)
+            .map(|value| std::char::from_u32(value).unwrap())
+    });
+
+    // every character is East Asian Wide
+    let chars = chars.map(|code_point| (code_point, format.measure.char_width(code_point, |_| 2)));
+    write_lines(chars, buf, format);
+}
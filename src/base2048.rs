@@ -0,0 +1,555 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A sibling of base65536 modelled on [qntm]'s [base2048], for Twitter.
+//!
+//! Twitter counts most characters twice towards the length of a tweet,
+//! including every base65536 character, but counts characters below U+1100
+//! once. Every base2048 character is one of those, and carries 11 bits. The
+//! final character may instead carry only 3 bits, as one of the digits `0`
+//! to `7`, and any bits left over at the end are ones.
+//!
+//! The other 2,048 characters are the letters and numbers below U+1100 that
+//! were assigned by Unicode 10.0 and have no decomposition, and so survive
+//! any normalization, in order of code point.
+//!
+//! As every character, including the final one, counts once towards
+//! [`twitter::weighted_len`], a tweet can carry up to 385 octets, where
+//! base65536 manages 280.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::base2048::{decode, encode};
+//! use base65536::twitter;
+//!
+//! let encoded = encode("hello world", None);
+//! assert_eq!("ڵϠɲණæஊಢࢷ", encoded);
+//! assert_eq!(8, twitter::weighted_len(&encoded));
+//! assert_eq!(b"hello world", &decode(&encoded, false)?[..]);
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+//!
+//! [qntm]: https://qntm.org/
+//! [base2048]: https://github.com/qntm/base2048
+//! [`twitter::weighted_len`]: ../twitter/fn.weighted_len.html
+
+pub use crate::WrapOptions;
+
+use crate::packed::{self, Chars, Fault, Packing};
+use crate::{write_lines, Hasher};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::{error, fmt};
+
+// Every range of characters carrying 11 bits, first and last inclusive. The
+// bits are the index of a character among all of these.
+const RANGES: &[(u32, u32)] = &[
+    (0x0038, 0x0039),
+    (0x0041, 0x005A),
+    (0x0061, 0x007A),
+    (0x00C6, 0x00C6),
+    (0x00D0, 0x00D0),
+    (0x00D8, 0x00D8),
+    (0x00DE, 0x00DF),
+    (0x00E6, 0x00E6),
+    (0x00F0, 0x00F0),
+    (0x00F8, 0x00F8),
+    (0x00FE, 0x00FE),
+    (0x0110, 0x0111),
+    (0x0126, 0x0127),
+    (0x0131, 0x0131),
+    (0x0138, 0x0138),
+    (0x0141, 0x0142),
+    (0x014A, 0x014B),
+    (0x0152, 0x0153),
+    (0x0166, 0x0167),
+    (0x0180, 0x019F),
+    (0x01A2, 0x01AE),
+    (0x01B1, 0x01C3),
+    (0x01DD, 0x01DD),
+    (0x01E4, 0x01E5),
+    (0x01F6, 0x01F7),
+    (0x021C, 0x021D),
+    (0x0220, 0x0225),
+    (0x0234, 0x02AF),
+    (0x0370, 0x0373),
+    (0x0376, 0x0377),
+    (0x037B, 0x037D),
+    (0x037F, 0x037F),
+    (0x0391, 0x03A1),
+    (0x03A3, 0x03A9),
+    (0x03B1, 0x03C9),
+    (0x03CF, 0x03CF),
+    (0x03D7, 0x03EF),
+    (0x03F3, 0x03F3),
+    (0x03F7, 0x03F8),
+    (0x03FA, 0x03FF),
+    (0x0402, 0x0402),
+    (0x0404, 0x0406),
+    (0x0408, 0x040B),
+    (0x040F, 0x0418),
+    (0x041A, 0x0438),
+    (0x043A, 0x044F),
+    (0x0452, 0x0452),
+    (0x0454, 0x0456),
+    (0x0458, 0x045B),
+    (0x045F, 0x0475),
+    (0x0478, 0x0481),
+    (0x048A, 0x04C0),
+    (0x04C3, 0x04CF),
+    (0x04D4, 0x04D5),
+    (0x04D8, 0x04D9),
+    (0x04E0, 0x04E1),
+    (0x04E8, 0x04E9),
+    (0x04F6, 0x04F7),
+    (0x04FA, 0x052F),
+    (0x0531, 0x0556),
+    (0x0561, 0x0586),
+    (0x05D0, 0x05EA),
+    (0x05F0, 0x05F2),
+    (0x0620, 0x0621),
+    (0x0627, 0x063F),
+    (0x0641, 0x064A),
+    (0x0660, 0x0669),
+    (0x066E, 0x066F),
+    (0x0671, 0x0674),
+    (0x0679, 0x06BF),
+    (0x06C1, 0x06C1),
+    (0x06C3, 0x06D2),
+    (0x06D5, 0x06D5),
+    (0x06EE, 0x06FC),
+    (0x06FF, 0x06FF),
+    (0x0710, 0x0710),
+    (0x0712, 0x072F),
+    (0x074D, 0x07A5),
+    (0x07B1, 0x07B1),
+    (0x07C0, 0x07EA),
+    (0x0800, 0x0815),
+    (0x0840, 0x0858),
+    (0x0860, 0x086A),
+    (0x08A0, 0x08B4),
+    (0x08B6, 0x08BD),
+    (0x0904, 0x0928),
+    (0x092A, 0x0930),
+    (0x0932, 0x0933),
+    (0x0935, 0x0939),
+    (0x093D, 0x093D),
+    (0x0950, 0x0950),
+    (0x0960, 0x0961),
+    (0x0966, 0x096F),
+    (0x0972, 0x0980),
+    (0x0985, 0x098C),
+    (0x098F, 0x0990),
+    (0x0993, 0x09A8),
+    (0x09AA, 0x09B0),
+    (0x09B2, 0x09B2),
+    (0x09B6, 0x09B9),
+    (0x09BD, 0x09BD),
+    (0x09CE, 0x09CE),
+    (0x09E0, 0x09E1),
+    (0x09E6, 0x09F1),
+    (0x09F4, 0x09F9),
+    (0x09FC, 0x09FC),
+    (0x0A05, 0x0A0A),
+    (0x0A0F, 0x0A10),
+    (0x0A13, 0x0A28),
+    (0x0A2A, 0x0A30),
+    (0x0A32, 0x0A32),
+    (0x0A35, 0x0A35),
+    (0x0A38, 0x0A39),
+    (0x0A5C, 0x0A5C),
+    (0x0A66, 0x0A6F),
+    (0x0A72, 0x0A74),
+    (0x0A85, 0x0A8D),
+    (0x0A8F, 0x0A91),
+    (0x0A93, 0x0AA8),
+    (0x0AAA, 0x0AB0),
+    (0x0AB2, 0x0AB3),
+    (0x0AB5, 0x0AB9),
+    (0x0ABD, 0x0ABD),
+    (0x0AD0, 0x0AD0),
+    (0x0AE0, 0x0AE1),
+    (0x0AE6, 0x0AEF),
+    (0x0AF9, 0x0AF9),
+    (0x0B05, 0x0B0C),
+    (0x0B0F, 0x0B10),
+    (0x0B13, 0x0B28),
+    (0x0B2A, 0x0B30),
+    (0x0B32, 0x0B33),
+    (0x0B35, 0x0B39),
+    (0x0B3D, 0x0B3D),
+    (0x0B5F, 0x0B61),
+    (0x0B66, 0x0B6F),
+    (0x0B71, 0x0B77),
+    (0x0B83, 0x0B83),
+    (0x0B85, 0x0B8A),
+    (0x0B8E, 0x0B90),
+    (0x0B92, 0x0B93),
+    (0x0B95, 0x0B95),
+    (0x0B99, 0x0B9A),
+    (0x0B9C, 0x0B9C),
+    (0x0B9E, 0x0B9F),
+    (0x0BA3, 0x0BA4),
+    (0x0BA8, 0x0BAA),
+    (0x0BAE, 0x0BB9),
+    (0x0BD0, 0x0BD0),
+    (0x0BE6, 0x0BF2),
+    (0x0C05, 0x0C0C),
+    (0x0C0E, 0x0C10),
+    (0x0C12, 0x0C28),
+    (0x0C2A, 0x0C39),
+    (0x0C3D, 0x0C3D),
+    (0x0C58, 0x0C5A),
+    (0x0C60, 0x0C61),
+    (0x0C66, 0x0C6F),
+    (0x0C78, 0x0C7E),
+    (0x0C80, 0x0C80),
+    (0x0C85, 0x0C8C),
+    (0x0C8E, 0x0C90),
+    (0x0C92, 0x0CA8),
+    (0x0CAA, 0x0CB3),
+    (0x0CB5, 0x0CB9),
+    (0x0CBD, 0x0CBD),
+    (0x0CDE, 0x0CDE),
+    (0x0CE0, 0x0CE1),
+    (0x0CE6, 0x0CEF),
+    (0x0CF1, 0x0CF2),
+    (0x0D05, 0x0D0C),
+    (0x0D0E, 0x0D10),
+    (0x0D12, 0x0D3A),
+    (0x0D3D, 0x0D3D),
+    (0x0D4E, 0x0D4E),
+    (0x0D54, 0x0D56),
+    (0x0D58, 0x0D61),
+    (0x0D66, 0x0D78),
+    (0x0D7A, 0x0D7F),
+    (0x0D85, 0x0D96),
+    (0x0D9A, 0x0DB1),
+    (0x0DB3, 0x0DBB),
+    (0x0DBD, 0x0DBD),
+    (0x0DC0, 0x0DC6),
+    (0x0DE6, 0x0DEF),
+    (0x0E01, 0x0E30),
+    (0x0E32, 0x0E32),
+    (0x0E40, 0x0E45),
+    (0x0E50, 0x0E59),
+    (0x0E81, 0x0E82),
+    (0x0E84, 0x0E84),
+    (0x0E87, 0x0E88),
+    (0x0E8A, 0x0E8A),
+    (0x0E8D, 0x0E8D),
+    (0x0E94, 0x0E97),
+    (0x0E99, 0x0E9F),
+    (0x0EA1, 0x0EA3),
+    (0x0EA5, 0x0EA5),
+    (0x0EA7, 0x0EA7),
+    (0x0EAA, 0x0EAB),
+    (0x0EAD, 0x0EB0),
+    (0x0EB2, 0x0EB2),
+    (0x0EBD, 0x0EBD),
+    (0x0EC0, 0x0EC4),
+    (0x0ED0, 0x0ED9),
+    (0x0EDE, 0x0EDF),
+    (0x0F00, 0x0F00),
+    (0x0F20, 0x0F33),
+    (0x0F40, 0x0F42),
+    (0x0F44, 0x0F47),
+    (0x0F49, 0x0F4C),
+    (0x0F4E, 0x0F51),
+    (0x0F53, 0x0F56),
+    (0x0F58, 0x0F5B),
+    (0x0F5D, 0x0F68),
+    (0x0F6A, 0x0F6C),
+    (0x0F88, 0x0F8C),
+    (0x1000, 0x1025),
+    (0x1027, 0x102A),
+    (0x103F, 0x1049),
+    (0x1050, 0x1055),
+];
+// The characters carrying the final 3 bits.
+const FINAL_RANGE: (u32, u32) = (0x30, 0x37);
+lazy_static! {
+    static ref REPERTOIRE: Vec<char> = RANGES
+        .iter()
+        .flat_map(|&(first, last)| first..=last)
+        .filter_map(std::char::from_u32)
+        .collect();
+    static ref CHAR_TO_INDEX: HashMap<char, u16, Hasher> = REPERTOIRE
+        .iter()
+        .enumerate()
+        .map(|(index, &code_point)| (code_point, index as u16))
+        .collect();
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while decoding.
+///
+/// Used with [`decode`], [`decode_buf`] and [`decode_slice`]. See them for
+/// examples.
+///
+/// [`decode`]: fn.decode.html
+/// [`decode_buf`]: fn.decode_buf.html
+/// [`decode_slice`]: fn.decode_slice.html
+pub enum Error {
+    /// A code point not valid in base2048 was found in the input stream.
+    /// Consider using the `ignore_garbage` option.
+    ///
+    /// Contains the offset from the beginning of the stream at which the
+    /// invalid code point was found, and the actual code point.
+    InvalidCodePoint(usize, char),
+    /// The base2048 stream continued after a final 3-bit character.
+    InvalidLength,
+    /// The bits left over at the end of the stream weren't all ones.
+    InvalidPadding,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidCodePoint(offset, ch) => {
+                write!(f, "invalid code point '{}' at offset {}", ch, offset)
+            }
+            Error::InvalidLength => write!(f, "sequence continued after final character"),
+            Error::InvalidPadding => write!(f, "padding bits were not all ones"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::InvalidCodePoint(_, _) => "invalid code point",
+            Error::InvalidLength => "invalid length",
+            Error::InvalidPadding => "invalid padding",
+        }
+    }
+}
+
+/// A specialized [`Result`] type for decoding operations.
+///
+/// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
+pub type DecodeResult<T> = ::std::result::Result<T, Error>;
+
+// The bit packing of base2048.
+struct Base2048;
+
+impl Packing for Base2048 {
+    const BITS: u32 = 11;
+    const FINAL_BITS: u32 = 3;
+
+    #[inline]
+    fn encode(bits: u32, is_final: bool) -> char {
+        if is_final {
+            // It is safe to unwrap because the final range is ASCII digits.
+            std::char::from_u32(FINAL_RANGE.0 + bits).unwrap()
+        } else {
+            REPERTOIRE[bits as usize]
+        }
+    }
+
+    #[inline]
+    fn decode(code_point: char) -> Option<(u32, bool)> {
+        let value = code_point as u32;
+        if value >= FINAL_RANGE.0 && value <= FINAL_RANGE.1 {
+            Some((value - FINAL_RANGE.0, true))
+        } else {
+            CHAR_TO_INDEX
+                .get(&code_point)
+                .map(|&index| (u32::from(index), false))
+        }
+    }
+}
+
+#[inline]
+fn inner_decode<F>(input: &str, ignore_garbage: bool, out: F) -> DecodeResult<()>
+where
+    F: FnMut(u8),
+{
+    packed::decode::<Base2048, _>(input, ignore_garbage, out).map_err(|fault| match fault {
+        Fault::CodePoint(index, code_point) => Error::InvalidCodePoint(index, code_point),
+        Fault::Length => Error::InvalidLength,
+        Fault::Padding => Error::InvalidPadding,
+    })
+}
+
+/// Decode from a reference to a base2048-encoded string as octets.
+///
+/// # Errors
+///
+/// If the input string contains a character that isn't base2048,
+/// [`Error::InvalidCodePoint`] will be returned, along with the bad character,
+/// and its position in the input. As with base65536, this is *very* strict
+/// by default, even failing on line breaks. To prevent this, use with the
+/// `ignore_garbage` option.
+///
+/// If the base2048 stream continues after a final 3-bit character,
+/// [`Error::InvalidLength`] is returned, and if the bits left over at the end
+/// aren't all ones, [`Error::InvalidPadding`] is.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::base2048::decode;
+///
+/// assert_eq!(vec![1, 2, 3], decode("GƷ7", false)?);
+/// assert_eq!("hello world", String::from_utf8(decode("ڵϠɲණæஊಢࢷ", false)?)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+/// [`Error::InvalidLength`]: enum.Error.html#variant.InvalidLength
+/// [`Error::InvalidPadding`]: enum.Error.html#variant.InvalidPadding
+pub fn decode<T>(input: &T, ignore_garbage: bool) -> DecodeResult<Vec<u8>>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let mut buf = Vec::with_capacity(input.as_ref().len());
+    decode_buf(input, &mut buf, ignore_garbage).map(|_| buf)
+}
+
+/// Decode from a reference to a base2048-encoded string as octets.
+/// Writes into the supplied output buffer, growing it if needed.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::base2048::decode_buf;
+///
+/// let mut buf = Vec::new();
+/// decode_buf("ڵϠɲණæஊಢࢷ", &mut buf, false)?;
+/// assert_eq!("hello world", String::from_utf8(buf)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: fn.decode.html
+pub fn decode_buf<T>(input: &T, buf: &mut Vec<u8>, ignore_garbage: bool) -> DecodeResult<()>
+where
+    T: ?Sized + AsRef<str>,
+{
+    inner_decode(input.as_ref(), ignore_garbage, |byte| buf.push(byte))
+}
+
+/// Decode from a reference to a base2048-encoded string as octets.
+/// Writes into the supplied slice, returning how many bytes were written.
+///
+/// # Panics
+///
+/// Panics if the slice is not long enough.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::base2048::decode_slice;
+///
+/// let mut buf = [0; 11];
+/// decode_slice("ڵϠɲණæஊಢࢷ", &mut buf, false)?;
+/// assert_eq!(b"hello world", &buf);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: fn.decode.html
+pub fn decode_slice<T>(input: &T, buf: &mut [u8], ignore_garbage: bool) -> DecodeResult<usize>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let mut pos = 0;
+    inner_decode(input.as_ref(), ignore_garbage, |byte| {
+        buf[pos] = byte;
+        pos += 1;
+    })
+    .map(|_| pos)
+}
+
+/// Encode arbitrary octets as base2048.
+///
+/// The `wrap` option allows wrapping the output every so many characters,
+/// optionally with a supplied string using [`WrapOptions`]. The characters
+/// of any end-of-line string or prefix shouldn't be base2048.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::base2048::{encode, WrapOptions};
+///
+/// assert_eq!("ڵϠɲණæஊಢࢷ", encode("hello world", None));
+/// assert_eq!("ڵϠɲණ\r\næஊಢࢷ", encode("hello world", WrapOptions::WrapAtWith(4, "\r\n")));
+/// ```
+///
+/// [`WrapOptions`]: ../enum.WrapOptions.html
+pub fn encode<'a, T, W>(input: &T, wrap: W) -> String
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    // Every 11 bits of input becomes a character of up to three bytes. This
+    // allocates for the unwrapped output.
+    let mut output = String::with_capacity((input.as_ref().len() * 8 / 11 + 1) * 3);
+    encode_buf(input, &mut output, wrap);
+    output
+}
+
+/// Encode arbitrary octets as base2048. Writes into the supplied output
+/// buffer, growing it if needed.
+///
+/// The `wrap` option allows wrapping the output every so many characters,
+/// optionally with a supplied string using [`WrapOptions`]. The characters
+/// of any end-of-line string or prefix shouldn't be base2048.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::base2048::encode_buf;
+///
+/// let mut buf = String::new();
+/// encode_buf("hello world", &mut buf, 4);
+///
+/// assert_eq!("ڵϠɲණ\næஊಢࢷ", buf);
+/// ```
+///
+/// [`WrapOptions`]: ../enum.WrapOptions.html
+pub fn encode_buf<'a, T, W>(input: &T, buf: &mut String, wrap: W)
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    let format = wrap.into().line_format();
+    let chars = Chars::<Base2048>::new(input.as_ref())
+        .map(|code_point| (code_point, format.measure.char_width(code_point, |_| 1)));
+    write_lines(chars, buf, format);
+}
@@ -0,0 +1,136 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Why base64 couldn't be decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Fault {
+    /// A character that isn't base64, or isn't allowed where it was.
+    CodePoint,
+    /// The input ended part of the way through a character, the padding
+    /// didn't make up a group of four, or the final bits weren't zero.
+    Padding,
+}
+
+/// Decodes base64 a character at a time, with either the standard or URL-safe
+/// alphabet, and with or without padding.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Decoder {
+    bits: u32,
+    len: u32,
+    digits: usize,
+    padding: usize,
+}
+
+impl Decoder {
+    /// Decodes the next character, returning an octet if one was completed.
+    #[inline]
+    pub(crate) fn push(&mut self, code_point: char) -> Result<Option<u8>, Fault> {
+        if code_point == '=' {
+            // one padding character after three digits, or two after two
+            self.padding += 1;
+            return match self.digits % 4 {
+                2 | 3 if self.digits % 4 + self.padding <= 4 => Ok(None),
+                _ => Err(Fault::CodePoint),
+            };
+        } else if self.padding > 0 {
+            return Err(Fault::CodePoint);
+        }
+
+        let value = match code_point {
+            'A'..='Z' => code_point as u32 - 'A' as u32,
+            'a'..='z' => code_point as u32 - 'a' as u32 + 26,
+            '0'..='9' => code_point as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(Fault::CodePoint),
+        };
+        self.digits += 1;
+        self.bits = self.bits << 6 | value;
+        self.len += 6;
+
+        if self.len >= 8 {
+            self.len -= 8;
+            let byte = (self.bits >> self.len) as u8;
+            self.bits &= (1 << self.len) - 1;
+            Ok(Some(byte))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Checks that the input ended where it could have.
+    pub(crate) fn finish(&self) -> Result<(), Fault> {
+        let complete = match self.digits % 4 {
+            1 => false,
+            0 => self.padding == 0,
+            rest => self.padding == 0 || rest + self.padding == 4,
+        };
+
+        if complete && self.bits == 0 {
+            Ok(())
+        } else {
+            Err(Fault::Padding)
+        }
+    }
+}
+
+/// Decodes a whole string of base64.
+pub(crate) fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut decoder = Decoder::default();
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    for code_point in input.chars() {
+        output.extend(decoder.push(code_point).ok()?);
+    }
+    decoder.finish().ok().map(|_| output)
+}
+
+/// Encodes octets as standard, padded base64, an octet at a time.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Encoder {
+    bits: u32,
+    len: u32,
+}
+
+impl Encoder {
+    /// Encodes the next octet, writing any completed characters.
+    #[inline]
+    pub(crate) fn push(&mut self, byte: u8, buf: &mut String) {
+        self.bits = self.bits << 8 | u32::from(byte);
+        self.len += 8;
+        while self.len >= 6 {
+            self.len -= 6;
+            buf.push(char::from(
+                ALPHABET[(self.bits >> self.len) as usize & 0x3F],
+            ));
+        }
+        self.bits &= (1 << self.len) - 1;
+    }
+
+    /// Writes the bits left over, and padding.
+    pub(crate) fn finish(self, buf: &mut String) {
+        match self.len {
+            2 => {
+                buf.push(char::from(ALPHABET[(self.bits << 4) as usize]));
+                buf.push_str("==");
+            }
+            4 => {
+                buf.push(char::from(ALPHABET[(self.bits << 2) as usize]));
+                buf.push('=');
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{encode_buf, Measure, WrapOptions};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A limit on the length of encoded output.
+///
+/// Used with [`encode_prefix_within`]. See it for examples.
+///
+/// [`encode_prefix_within`]: fn.encode_prefix_within.html
+pub enum Budget {
+    /// At most so many characters.
+    CodePoints(usize),
+    /// At most so many bytes, when encoded as UTF-8.
+    Utf8Len(usize),
+    /// At most so many code units, when encoded as UTF-16.
+    Utf16Len(usize),
+}
+
+impl Budget {
+    fn split(self) -> (usize, Measure) {
+        match self {
+            Budget::CodePoints(limit) => (limit, Measure::CodePoints),
+            Budget::Utf8Len(limit) => (limit, Measure::Utf8),
+            Budget::Utf16Len(limit) => (limit, Measure::Utf16),
+        }
+    }
+
+    /// Returns what remains of this budget after setting aside enough for
+    /// `chars` characters of any block, or `None` if it isn't enough.
+    pub(crate) fn reserve(self, chars: usize) -> Option<Budget> {
+        let (limit, measure) = self.split();
+        let limit = limit.checked_sub(chars * measure.max_width())?;
+        Some(match self {
+            Budget::CodePoints(_) => Budget::CodePoints(limit),
+            Budget::Utf8Len(_) => Budget::Utf8Len(limit),
+            Budget::Utf16Len(_) => Budget::Utf16Len(limit),
+        })
+    }
+}
+
+/// Returns how many octets from the start of `input` can be encoded within
+/// `budget`.
+pub(crate) fn prefix_len_within(input: &[u8], budget: Budget) -> usize {
+    let (mut remaining, measure) = budget.split();
+
+    let mut consumed = 0;
+    for bytes in input.chunks(2) {
+        let width = measure.width(bytes.get(1).cloned());
+        if width <= remaining {
+            remaining -= width;
+            consumed += bytes.len();
+        } else {
+            // a lone padding character may still fit where a pair didn't,
+            // if the pair's block is outside of the BMP
+            if bytes.len() == 2 && measure.width(None) <= remaining {
+                consumed += 1;
+            }
+            break;
+        }
+    }
+
+    consumed
+}
+
+/// Encode as many octets from the start of the input as will fit within a
+/// limited length, as base65536.
+///
+/// Returns the encoded string, and the number of octets of input that it
+/// contains. The string is exactly what [`encode`] would return for that
+/// many octets of input, so it decodes as usual.
+///
+/// The length of each character is known in advance, so this never encodes
+/// more than it returns. Since characters outside of the BMP are longer in
+/// both UTF-8 and UTF-16, a single octet may fit at the end where a pair of
+/// them doesn't. If so, that octet is encoded with a padding character, which
+/// ends the stream. Use [`decode_concatenated`] to decode the results of
+/// several calls joined together.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{encode_prefix_within, Budget};
+///
+/// let input = b"hello world";
+///
+/// let (encoded, consumed) = encode_prefix_within(input, Budget::CodePoints(3));
+/// assert_eq!(("驨ꍬ啯", 6), (&encoded[..], consumed));
+///
+/// // U+12077 takes up four bytes in UTF-8, so "w" gets a padding character
+/// let (encoded, consumed) = encode_prefix_within(input, Budget::Utf8Len(12));
+/// assert_eq!(("驨ꍬ啯ᕷ", 7), (&encoded[..], consumed));
+///
+/// let (encoded, consumed) = encode_prefix_within(input, Budget::Utf16Len(140));
+/// assert_eq!(("驨ꍬ啯𒁷ꍲᕤ", 11), (&encoded[..], consumed));
+/// ```
+///
+/// [`encode`]: fn.encode.html
+/// [`decode_concatenated`]: fn.decode_concatenated.html
+pub fn encode_prefix_within<T>(input: &T, budget: Budget) -> (String, usize)
+where
+    T: ?Sized + AsRef<[u8]>,
+{
+    let input = input.as_ref();
+    let consumed = prefix_len_within(input, budget);
+
+    let mut output = String::with_capacity(consumed * 2);
+    encode_buf(&input[..consumed], &mut output, WrapOptions::NoWrap);
+    (output, consumed)
+}
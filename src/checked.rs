@@ -0,0 +1,289 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode_code_point, encode_buf, gf, Error, WrapOptions};
+use lazy_static::lazy_static;
+use std::{error, fmt};
+
+// The checksum is made up of this many characters, which are the parity
+// symbols of a Reed–Solomon code over GF(2^16). The code has a distance of
+// four, so it detects any three substituted characters, and any single one
+// can be located.
+const CHECK_LEN: usize = 3;
+
+// The most data symbols a codeword can hold, after the symbol saying whether
+// the data is padded and the checksum. Past this the code no longer has a
+// distance of four.
+const MAX_SYMBOLS: usize = gf::ORDER - CHECK_LEN - 1;
+
+/// The most octets that can be encoded with a single checksum by
+/// [`encode_checked`].
+///
+/// [`encode_checked`]: fn.encode_checked.html
+pub const MAX_CHECKED_LEN: usize = MAX_SYMBOLS * 2;
+
+lazy_static! {
+    static ref GENERATOR: Vec<u16> = gf::generator(CHECK_LEN);
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while encoding or decoding checksummed base65536.
+///
+/// Used with [`encode_checked`] and [`decode_checked`]. See them for examples.
+///
+/// [`encode_checked`]: fn.encode_checked.html
+/// [`decode_checked`]: fn.decode_checked.html
+pub enum CheckedError {
+    /// The input wasn't valid base65536.
+    Decode(Error),
+    /// The input was too short to contain a checksum.
+    TooShort,
+    /// The input was too long to be covered by a single checksum. No more
+    /// than [`MAX_CHECKED_LEN`] octets can be encoded.
+    ///
+    /// [`MAX_CHECKED_LEN`]: constant.MAX_CHECKED_LEN.html
+    TooLong,
+    /// The checksum didn't match the data.
+    ///
+    /// If the mismatch can be explained by a single substituted character,
+    /// contains the offset from the beginning of the stream at which that
+    /// character is.
+    ChecksumMismatch(Option<usize>),
+}
+
+impl fmt::Display for CheckedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CheckedError::Decode(ref err) => write!(f, "{}", err),
+            CheckedError::TooShort => write!(f, "too short to contain a checksum"),
+            CheckedError::TooLong => write!(f, "too long to be checksummed"),
+            CheckedError::ChecksumMismatch(Some(offset)) => {
+                write!(f, "checksum mismatch, likely at offset {}", offset)
+            }
+            CheckedError::ChecksumMismatch(None) => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+impl error::Error for CheckedError {
+    fn description(&self) -> &str {
+        match *self {
+            CheckedError::Decode(_) => "invalid base65536",
+            CheckedError::TooShort => "too short",
+            CheckedError::TooLong => "too long",
+            CheckedError::ChecksumMismatch(_) => "checksum mismatch",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            CheckedError::Decode(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// The checksummed message starts with a symbol telling whether the data ends
+// in a padding character, as otherwise replacing that character with one
+// from the first block would go unnoticed.
+fn message<I>(symbols: I, padded: bool) -> impl Iterator<Item = u16>
+where
+    I: IntoIterator<Item = u16>,
+{
+    Some(padded as u16).into_iter().chain(symbols)
+}
+
+fn symbol(bytes: &[u8]) -> u16 {
+    u16::from(bytes[0]) | bytes.get(1).map_or(0, |&byte| u16::from(byte) << 8)
+}
+
+// Returns the index in the codeword of the symbol that, if it alone was
+// substituted, would explain its syndromes.
+fn locate(codeword: &[u16]) -> Option<usize> {
+    // A single error of e at the symbol multiplied by x^p gives syndromes of
+    // e·α^p, e·α^2p and e·α^3p, which are consistent only if S1·S3 = S2².
+    let syndromes = gf::syndromes(codeword, CHECK_LEN);
+    let (s1, s2, s3) = (syndromes[0], syndromes[1], syndromes[2]);
+    if s1 != 0 && s2 != 0 && gf::mul(s1, s3) == gf::mul(s2, s2) {
+        let power = gf::log(gf::div(s2, s1));
+        if power < codeword.len() {
+            return Some(codeword.len() - 1 - power);
+        }
+    }
+    None
+}
+
+/// Encode arbitrary octets as base65536, with a checksum.
+///
+/// The checksum takes up the first three characters of the output, and is
+/// followed by the data, exactly as [`encode`] would encode it. Use
+/// [`decode_checked`] to decode it.
+///
+/// The checksum comes first, rather than being appended, because data of an
+/// odd length ends in a padding character, and nothing may follow that in a
+/// base65536 stream. Putting it first keeps the whole output valid
+/// base65536, so that it still passes through anything expecting that.
+///
+/// # Errors
+///
+/// Returns [`CheckedError::TooLong`] if the input is longer than
+/// [`MAX_CHECKED_LEN`] octets, as the checksum can't guarantee detecting
+/// errors past that.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::{decode_checked, encode_checked};
+///
+/// let encoded = encode_checked("hello world", None)?;
+/// assert_eq!(9, encoded.chars().count());
+/// assert!(encoded.ends_with("驨ꍬ啯𒁷ꍲᕤ"));
+/// assert_eq!(b"hello world", &decode_checked(&encoded, false)?[..]);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`encode`]: fn.encode.html
+/// [`decode_checked`]: fn.decode_checked.html
+/// [`CheckedError::TooLong`]: enum.CheckedError.html#variant.TooLong
+/// [`MAX_CHECKED_LEN`]: constant.MAX_CHECKED_LEN.html
+pub fn encode_checked<'a, T, W>(input: &T, wrap: W) -> Result<String, CheckedError>
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    let input = input.as_ref();
+    if input.len() > MAX_CHECKED_LEN {
+        return Err(CheckedError::TooLong);
+    }
+
+    let symbols = input.chunks(2).map(symbol);
+    let checksum = gf::remainder(message(symbols, input.len() % 2 == 1), &GENERATOR);
+
+    let mut bytes = Vec::with_capacity(CHECK_LEN * 2 + input.len());
+    for symbol in checksum {
+        bytes.extend_from_slice(&symbol.to_le_bytes());
+    }
+    bytes.extend_from_slice(input);
+
+    let mut output = String::with_capacity(bytes.len() * 2);
+    encode_buf(&bytes, &mut output, wrap);
+    Ok(output)
+}
+
+/// Decode checksummed base65536, as created by [`encode_checked`].
+///
+/// The checksum detects any substitution of up to three characters, and,
+/// if the damage looks like a single substituted character, the error
+/// points at it.
+///
+/// # Errors
+///
+/// The same as [`decode`], wrapped in [`CheckedError::Decode`]. If the input
+/// is too short to contain a checksum, [`CheckedError::TooShort`] is
+/// returned, and if it's too long to have been created by [`encode_checked`],
+/// [`CheckedError::TooLong`] is. If the checksum doesn't match the data,
+/// [`CheckedError::ChecksumMismatch`] is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{decode_checked, encode_checked, CheckedError};
+///
+/// let encoded = encode_checked("hello world", None).unwrap();
+/// let damaged = encoded.replace("啯", "啰");
+/// let offset = damaged.find("啰");
+/// assert_eq!(
+///     Err(CheckedError::ChecksumMismatch(offset)),
+///     decode_checked(&damaged, false)
+/// );
+/// ```
+///
+/// [`decode`]: fn.decode.html
+/// [`encode_checked`]: fn.encode_checked.html
+/// [`CheckedError::Decode`]: enum.CheckedError.html#variant.Decode
+/// [`CheckedError::TooShort`]: enum.CheckedError.html#variant.TooShort
+/// [`CheckedError::TooLong`]: enum.CheckedError.html#variant.TooLong
+/// [`CheckedError::ChecksumMismatch`]: enum.CheckedError.html#variant.ChecksumMismatch
+pub fn decode_checked<T>(input: &T, ignore_garbage: bool) -> Result<Vec<u8>, CheckedError>
+where
+    T: ?Sized + AsRef<str>,
+{
+    // the offset in the input of every character, and the symbol it carries
+    let mut symbols = Vec::with_capacity(input.as_ref().len() / 3);
+    let mut padded = false;
+    for (index, code_point) in input.as_ref().char_indices() {
+        match decode_code_point(code_point) {
+            Some(_) if padded => return Err(CheckedError::Decode(Error::InvalidLength)),
+            Some((byte1, byte2)) => {
+                padded = byte2.is_none();
+                symbols.push((index, symbol(&[byte1, byte2.unwrap_or(0)])));
+            }
+            None if ignore_garbage => {}
+            None => {
+                return Err(CheckedError::Decode(Error::InvalidCodePoint(
+                    index, code_point,
+                )))
+            }
+        }
+    }
+
+    if symbols.len() < CHECK_LEN || (padded && symbols.len() == CHECK_LEN) {
+        return Err(CheckedError::TooShort);
+    } else if symbols.len() > CHECK_LEN + MAX_SYMBOLS {
+        return Err(CheckedError::TooLong);
+    }
+
+    let (checksum, data) = symbols.split_at(CHECK_LEN);
+    let mut codeword: Vec<u16> = message(data.iter().map(|&(_, symbol)| symbol), padded)
+        .chain(checksum.iter().map(|&(_, symbol)| symbol))
+        .collect();
+
+    let syndromes = gf::syndromes(&codeword, CHECK_LEN);
+    if syndromes.iter().all(|&syndrome| syndrome == 0) {
+        let mut output = Vec::with_capacity(data.len() * 2);
+        for &(_, symbol) in data {
+            output.extend_from_slice(&symbol.to_le_bytes());
+        }
+        if padded {
+            output.pop();
+        }
+        return Ok(output);
+    }
+
+    // Substituting the final character may also change whether it's a
+    // padding character, and so the symbol saying whether the data was
+    // padded, which is a second error. If no single error explains the
+    // damage, try again as though the final character were the other kind,
+    // in which case the error can only be in that character.
+    let mut location = locate(&codeword);
+    if location.is_none() && !data.is_empty() {
+        codeword[0] ^= 1;
+        location = locate(&codeword).filter(|&index| index == data.len());
+    }
+
+    let location = location.and_then(|index| match index {
+        // only the kind of the final character was wrong
+        0 => data.last().map(|&(offset, _)| offset),
+        index if index <= data.len() => Some(data[index - 1].0),
+        index => Some(checksum[index - data.len() - 1].0),
+    });
+
+    Err(CheckedError::ChecksumMismatch(location))
+}
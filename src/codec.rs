@@ -0,0 +1,396 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A common interface to base65536 and its siblings, for choosing between
+//! them at runtime.
+//!
+//! Each encoding is a unit struct implementing [`Codec`], and [`AnyCodec`]
+//! can be any of them, such as one named in a configuration file. The trait
+//! is object safe, so `Box<dyn Codec>` works too.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::codec::{AnyCodec, Codec};
+//! use base65536::WrapOptions;
+//!
+//! for name in &["base65536", "base32768", "base2048", "base16384"] {
+//!     let codec = AnyCodec::from_name(name).unwrap();
+//!     let encoded = codec.encode(b"hello world", WrapOptions::NoWrap);
+//!     assert_eq!(codec.encoded_len(11), encoded.chars().count());
+//!     assert_eq!(b"hello world", &codec.decode(&encoded, false)?[..]);
+//! }
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+//!
+//! [`Codec`]: trait.Codec.html
+//! [`AnyCodec`]: enum.AnyCodec.html
+
+use crate::{base16384, base2048, base32768, WrapOptions};
+use std::{error, fmt};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while decoding with any [`Codec`].
+///
+/// Each variant means the same as that of the same name in the error type of
+/// the underlying encoding.
+///
+/// [`Codec`]: trait.Codec.html
+pub enum CodecError {
+    /// A code point not valid in the encoding was found in the input stream.
+    ///
+    /// Contains the offset from the beginning of the stream at which the
+    /// invalid code point was found, and the actual code point.
+    InvalidCodePoint(usize, char),
+    /// The stream continued after its end, or ended early.
+    InvalidLength,
+    /// The bits left over at the end of the stream were invalid.
+    InvalidPadding,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CodecError::InvalidCodePoint(offset, ch) => {
+                write!(f, "invalid code point '{}' at offset {}", ch, offset)
+            }
+            CodecError::InvalidLength => write!(f, "sequence has an invalid length"),
+            CodecError::InvalidPadding => write!(f, "padding bits were invalid"),
+        }
+    }
+}
+
+impl error::Error for CodecError {
+    fn description(&self) -> &str {
+        match *self {
+            CodecError::InvalidCodePoint(_, _) => "invalid code point",
+            CodecError::InvalidLength => "invalid length",
+            CodecError::InvalidPadding => "invalid padding",
+        }
+    }
+}
+
+impl From<crate::Error> for CodecError {
+    fn from(err: crate::Error) -> Self {
+        match err {
+            crate::Error::InvalidCodePoint(offset, ch) => CodecError::InvalidCodePoint(offset, ch),
+            crate::Error::InvalidLength => CodecError::InvalidLength,
+        }
+    }
+}
+
+impl From<base32768::Error> for CodecError {
+    fn from(err: base32768::Error) -> Self {
+        match err {
+            base32768::Error::InvalidCodePoint(offset, ch) => {
+                CodecError::InvalidCodePoint(offset, ch)
+            }
+            base32768::Error::InvalidLength => CodecError::InvalidLength,
+            base32768::Error::InvalidPadding => CodecError::InvalidPadding,
+        }
+    }
+}
+
+impl From<base2048::Error> for CodecError {
+    fn from(err: base2048::Error) -> Self {
+        match err {
+            base2048::Error::InvalidCodePoint(offset, ch) => {
+                CodecError::InvalidCodePoint(offset, ch)
+            }
+            base2048::Error::InvalidLength => CodecError::InvalidLength,
+            base2048::Error::InvalidPadding => CodecError::InvalidPadding,
+        }
+    }
+}
+
+impl From<base16384::Error> for CodecError {
+    fn from(err: base16384::Error) -> Self {
+        match err {
+            base16384::Error::InvalidCodePoint(offset, ch) => {
+                CodecError::InvalidCodePoint(offset, ch)
+            }
+            base16384::Error::InvalidLength => CodecError::InvalidLength,
+        }
+    }
+}
+
+/// An encoding of octets as Unicode text.
+pub trait Codec {
+    /// The name of the encoding, in lowercase, such as `"base65536"`.
+    fn name(&self) -> &'static str;
+
+    /// Encode arbitrary octets, writing into the supplied output buffer and
+    /// wrapping as set by `wrap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if set to wrap every 0 columns.
+    fn encode_buf(&self, input: &[u8], buf: &mut String, wrap: WrapOptions);
+
+    /// Decode a string as octets, writing into the supplied output buffer.
+    ///
+    /// # Errors
+    ///
+    /// The same as the `decode_buf` function of the encoding.
+    fn decode_buf(
+        &self,
+        input: &str,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> Result<(), CodecError>;
+
+    /// The number of characters that `len` octets encode to, without any
+    /// line wrapping.
+    fn encoded_len(&self, len: usize) -> usize;
+
+    /// The most octets that `chars` characters can decode to.
+    fn decoded_len(&self, chars: usize) -> usize;
+
+    /// Encode arbitrary octets, wrapping as set by `wrap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if set to wrap every 0 columns.
+    fn encode(&self, input: &[u8], wrap: WrapOptions) -> String {
+        let mut output = String::with_capacity(self.encoded_len(input.len()) * 4);
+        self.encode_buf(input, &mut output, wrap);
+        output
+    }
+
+    /// Decode a string as octets.
+    ///
+    /// # Errors
+    ///
+    /// The same as the `decode` function of the encoding.
+    fn decode(&self, input: &str, ignore_garbage: bool) -> Result<Vec<u8>, CodecError> {
+        let mut buf = Vec::with_capacity(self.decoded_len(input.chars().count()));
+        self.decode_buf(input, &mut buf, ignore_garbage)
+            .map(|_| buf)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Base65536, as encoded by the crate root.
+pub struct Base65536;
+
+impl Codec for Base65536 {
+    fn name(&self) -> &'static str {
+        "base65536"
+    }
+
+    fn encode_buf(&self, input: &[u8], buf: &mut String, wrap: WrapOptions) {
+        crate::encode_buf(input, buf, wrap)
+    }
+
+    fn decode_buf(
+        &self,
+        input: &str,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> Result<(), CodecError> {
+        crate::decode_buf(input, buf, ignore_garbage).map_err(CodecError::from)
+    }
+
+    fn encoded_len(&self, len: usize) -> usize {
+        len.div_ceil(2)
+    }
+
+    fn decoded_len(&self, chars: usize) -> usize {
+        chars * 2
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Base32768, as encoded by the [`base32768`] module.
+///
+/// [`base32768`]: ../base32768/index.html
+pub struct Base32768;
+
+impl Codec for Base32768 {
+    fn name(&self) -> &'static str {
+        "base32768"
+    }
+
+    fn encode_buf(&self, input: &[u8], buf: &mut String, wrap: WrapOptions) {
+        base32768::encode_buf(input, buf, wrap)
+    }
+
+    fn decode_buf(
+        &self,
+        input: &str,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> Result<(), CodecError> {
+        base32768::decode_buf(input, buf, ignore_garbage).map_err(CodecError::from)
+    }
+
+    fn encoded_len(&self, len: usize) -> usize {
+        (len * 8).div_ceil(15)
+    }
+
+    fn decoded_len(&self, chars: usize) -> usize {
+        chars * 15 / 8
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Base2048, as encoded by the [`base2048`] module.
+///
+/// [`base2048`]: ../base2048/index.html
+pub struct Base2048;
+
+impl Codec for Base2048 {
+    fn name(&self) -> &'static str {
+        "base2048"
+    }
+
+    fn encode_buf(&self, input: &[u8], buf: &mut String, wrap: WrapOptions) {
+        base2048::encode_buf(input, buf, wrap)
+    }
+
+    fn decode_buf(
+        &self,
+        input: &str,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> Result<(), CodecError> {
+        base2048::decode_buf(input, buf, ignore_garbage).map_err(CodecError::from)
+    }
+
+    fn encoded_len(&self, len: usize) -> usize {
+        (len * 8).div_ceil(11)
+    }
+
+    fn decoded_len(&self, chars: usize) -> usize {
+        chars * 11 / 8
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Base16384, as encoded by the [`base16384`] module.
+///
+/// [`base16384`]: ../base16384/index.html
+pub struct Base16384;
+
+impl Codec for Base16384 {
+    fn name(&self) -> &'static str {
+        "base16384"
+    }
+
+    fn encode_buf(&self, input: &[u8], buf: &mut String, wrap: WrapOptions) {
+        base16384::encode_buf(input, buf, wrap)
+    }
+
+    fn decode_buf(
+        &self,
+        input: &str,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> Result<(), CodecError> {
+        base16384::decode_buf(input, buf, ignore_garbage).map_err(CodecError::from)
+    }
+
+    fn encoded_len(&self, len: usize) -> usize {
+        // a short final group is followed by a tail marker
+        match len % 7 {
+            0 => len / 7 * 4,
+            rest => len / 7 * 4 + (rest * 8).div_ceil(14) + 1,
+        }
+    }
+
+    fn decoded_len(&self, chars: usize) -> usize {
+        chars * 14 / 8
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Any of the encodings, chosen at runtime.
+pub enum AnyCodec {
+    /// Base65536.
+    #[default]
+    Base65536,
+    /// Base32768.
+    Base32768,
+    /// Base2048.
+    Base2048,
+    /// Base16384.
+    Base16384,
+}
+
+impl AnyCodec {
+    /// Find an encoding by its [`name`], ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base65536::codec::{AnyCodec, Codec};
+    ///
+    /// assert_eq!(Some(AnyCodec::Base2048), AnyCodec::from_name("Base2048"));
+    /// assert_eq!(None, AnyCodec::from_name("base64"));
+    ///
+    /// let codec = AnyCodec::Base32768;
+    /// assert_eq!(Some(codec), AnyCodec::from_name(codec.name()));
+    /// ```
+    ///
+    /// [`name`]: trait.Codec.html#tymethod.name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            AnyCodec::Base65536,
+            AnyCodec::Base32768,
+            AnyCodec::Base2048,
+            AnyCodec::Base16384,
+        ]
+        .iter()
+        .cloned()
+        .find(|codec| codec.name().eq_ignore_ascii_case(name))
+    }
+
+    fn codec(self) -> &'static dyn Codec {
+        match self {
+            AnyCodec::Base65536 => &Base65536,
+            AnyCodec::Base32768 => &Base32768,
+            AnyCodec::Base2048 => &Base2048,
+            AnyCodec::Base16384 => &Base16384,
+        }
+    }
+}
+
+impl Codec for AnyCodec {
+    fn name(&self) -> &'static str {
+        self.codec().name()
+    }
+
+    fn encode_buf(&self, input: &[u8], buf: &mut String, wrap: WrapOptions) {
+        self.codec().encode_buf(input, buf, wrap)
+    }
+
+    fn decode_buf(
+        &self,
+        input: &str,
+        buf: &mut Vec<u8>,
+        ignore_garbage: bool,
+    ) -> Result<(), CodecError> {
+        self.codec().decode_buf(input, buf, ignore_garbage)
+    }
+
+    fn encoded_len(&self, len: usize) -> usize {
+        self.codec().encoded_len(len)
+    }
+
+    fn decoded_len(&self, chars: usize) -> usize {
+        self.codec().decoded_len(chars)
+    }
+}
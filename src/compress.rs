@@ -0,0 +1,334 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode, decode_code_point, encode_buf, Error, WrapOptions};
+use std::io::{self, Read};
+use std::{error, fmt};
+
+// Compressed data is tagged with a character from the Control Pictures
+// block, none of which are base65536, so untagged data is never mistaken for
+// compressed data.
+const TAG_START: u32 = 0x2400;
+const TAG_END: u32 = 0x2440;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+/// A compression algorithm, used with [`encode_compressed`].
+///
+/// Which algorithms are available depends on the features this crate was
+/// built with, so matching on this needs a wildcard arm.
+///
+/// [`encode_compressed`]: fn.encode_compressed.html
+pub enum Compression {
+    /// Raw DEFLATE, as described in RFC 1951.
+    #[cfg(feature = "deflate")]
+    Deflate,
+    /// Zstandard, at its default compression level.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// The character that compressed data begins with to show it was
+    /// compressed with this algorithm.
+    pub fn tag(self) -> char {
+        match self {
+            #[cfg(feature = "deflate")]
+            Compression::Deflate => '\u{2401}',
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => '\u{2402}',
+        }
+    }
+
+    fn from_tag(tag: char) -> Option<Self> {
+        match tag {
+            #[cfg(feature = "deflate")]
+            '\u{2401}' => Some(Compression::Deflate),
+            #[cfg(feature = "zstd")]
+            '\u{2402}' => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    fn compress(self, input: &[u8]) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "deflate")]
+            Compression::Deflate => {
+                use flate2::{write::DeflateEncoder, Compression as Level};
+                use std::io::Write;
+
+                let mut encoder = DeflateEncoder::new(Vec::new(), Level::default());
+                encoder
+                    .write_all(input)
+                    .and_then(|_| encoder.finish())
+                    .expect("compressing into memory cannot fail")
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                zstd::encode_all(input, 0).expect("compressing into memory cannot fail")
+            }
+        }
+    }
+
+    fn decompress(self, input: &[u8], limit: Option<usize>) -> Result<Vec<u8>, CompressError> {
+        let mut reader: Box<dyn Read + '_> = match self {
+            #[cfg(feature = "deflate")]
+            Compression::Deflate => Box::new(flate2::read::DeflateDecoder::new(input)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(
+                zstd::stream::read::Decoder::new(input).map_err(CompressError::Decompress)?,
+            ),
+        };
+
+        let mut output = Vec::new();
+        match limit {
+            // read one octet past the limit to tell if it was exceeded
+            Some(limit) => {
+                reader
+                    .take(limit as u64 + 1)
+                    .read_to_end(&mut output)
+                    .map_err(CompressError::Decompress)?;
+                if output.len() > limit {
+                    return Err(CompressError::TooLarge(limit));
+                }
+            }
+            None => {
+                reader
+                    .read_to_end(&mut output)
+                    .map_err(CompressError::Decompress)?;
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[derive(Debug)]
+/// Represents an error while decoding possibly compressed base65536.
+///
+/// Used with [`decode_auto`] and [`decode_auto_with_limit`]. See them for
+/// examples.
+///
+/// [`decode_auto`]: fn.decode_auto.html
+/// [`decode_auto_with_limit`]: fn.decode_auto_with_limit.html
+pub enum CompressError {
+    /// The input was not valid base65536.
+    Decode(Error),
+    /// The input was tagged with a codec that's unknown, or wasn't enabled
+    /// when this crate was built.
+    UnknownCodec(char),
+    /// The data couldn't be decompressed.
+    Decompress(io::Error),
+    /// The decoded data would be longer than the limit given to
+    /// [`decode_auto_with_limit`].
+    ///
+    /// Contains the limit.
+    ///
+    /// [`decode_auto_with_limit`]: fn.decode_auto_with_limit.html
+    TooLarge(usize),
+}
+
+impl fmt::Display for CompressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CompressError::Decode(ref err) => write!(f, "{}", err),
+            CompressError::UnknownCodec(tag) => write!(f, "unknown codec {}", tag),
+            CompressError::Decompress(ref err) => write!(f, "decompression failed: {}", err),
+            CompressError::TooLarge(limit) => {
+                write!(f, "decompressed data longer than {} octets", limit)
+            }
+        }
+    }
+}
+
+impl error::Error for CompressError {
+    fn description(&self) -> &str {
+        match *self {
+            CompressError::Decode(_) => "invalid base65536",
+            CompressError::UnknownCodec(_) => "unknown codec",
+            CompressError::Decompress(_) => "decompression failed",
+            CompressError::TooLarge(_) => "decompressed data too large",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            CompressError::Decode(ref err) => Some(err),
+            CompressError::UnknownCodec(_) => None,
+            CompressError::Decompress(ref err) => Some(err),
+            CompressError::TooLarge(_) => None,
+        }
+    }
+}
+
+/// Compress arbitrary octets, and encode them as base65536.
+///
+/// The output begins with a character naming the codec, which isn't counted
+/// when wrapping, and is followed by the compressed data, exactly as
+/// [`encode`] would encode it. Use [`decode_auto`] to decode it.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "deflate")] {
+/// use base65536::{decode_auto, encode_compressed, Compression};
+///
+/// let input = r#"{"level":"info","msg":"ok"}"#.repeat(100);
+/// let encoded = encode_compressed(&input, Compression::Deflate, None);
+/// assert!(encoded.starts_with(Compression::Deflate.tag()));
+/// assert!(encoded.chars().count() < input.len() / 20);
+/// assert_eq!(input.as_bytes(), &decode_auto(&encoded, false).unwrap()[..]);
+/// # }
+/// ```
+///
+/// [`encode`]: fn.encode.html
+/// [`decode_auto`]: fn.decode_auto.html
+pub fn encode_compressed<'a, T, W>(input: &T, codec: Compression, wrap: W) -> String
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    let compressed = codec.compress(input.as_ref());
+    let mut output = String::with_capacity(compressed.len() * 2 + 3);
+    output.push(codec.tag());
+    encode_buf(&compressed, &mut output, wrap);
+    output
+}
+
+/// Decode from a reference to a base65536-encoded string as octets,
+/// decompressing it if it was compressed.
+///
+/// Data from [`encode_compressed`] is decompressed, and anything else is
+/// decoded exactly as [`decode`] would decode it. If `ignore_garbage` is set,
+/// the codec's character may follow any garbage, such as leading whitespace.
+///
+/// The decompressed data may be far larger than the input, so use
+/// [`decode_auto_with_limit`] for input that isn't trusted.
+///
+/// # Errors
+///
+/// Returns [`CompressError::Decode`] in the same cases as [`decode`],
+/// [`CompressError::UnknownCodec`] if the data was compressed with a codec
+/// that isn't enabled, and [`CompressError::Decompress`] if the compressed
+/// data was corrupt.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{decode_auto, encode, CompressError};
+///
+/// assert_eq!(b"hello world", &decode_auto(&encode("hello world", None), false).unwrap()[..]);
+///
+/// match decode_auto("\u{243F}驨ꍬ啯", false) {
+///     Err(CompressError::UnknownCodec('\u{243F}')) => {}
+///     _ => panic!("should be an unknown codec"),
+/// }
+/// ```
+///
+/// [`decode`]: fn.decode.html
+/// [`encode_compressed`]: fn.encode_compressed.html
+/// [`decode_auto_with_limit`]: fn.decode_auto_with_limit.html
+/// [`CompressError::Decode`]: enum.CompressError.html#variant.Decode
+/// [`CompressError::UnknownCodec`]: enum.CompressError.html#variant.UnknownCodec
+/// [`CompressError::Decompress`]: enum.CompressError.html#variant.Decompress
+pub fn decode_auto<T>(input: &T, ignore_garbage: bool) -> Result<Vec<u8>, CompressError>
+where
+    T: ?Sized + AsRef<str>,
+{
+    inner_decode_auto(input.as_ref(), ignore_garbage, None)
+}
+
+/// Decode from a reference to a base65536-encoded string as octets,
+/// decompressing it if it was compressed, but only up to a limit.
+///
+/// This is the same as [`decode_auto`], except that decompression stops as
+/// soon as the output would be longer than `limit` octets, so that a small
+/// input can't expand to fill all of memory. Data that wasn't compressed is
+/// held to the same limit.
+///
+/// # Errors
+///
+/// The same as [`decode_auto`], and [`CompressError::TooLarge`] if the
+/// decoded data would be longer than `limit`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "deflate")] {
+/// use base65536::{decode_auto_with_limit, encode_compressed, Compression, CompressError};
+///
+/// let encoded = encode_compressed(&[0; 100_000][..], Compression::Deflate, None);
+/// assert_eq!(100_000, decode_auto_with_limit(&encoded, false, 100_000).unwrap().len());
+/// match decode_auto_with_limit(&encoded, false, 1000) {
+///     Err(CompressError::TooLarge(1000)) => {}
+///     _ => panic!("should be too large"),
+/// }
+/// # }
+/// ```
+///
+/// [`decode_auto`]: fn.decode_auto.html
+/// [`CompressError::TooLarge`]: enum.CompressError.html#variant.TooLarge
+pub fn decode_auto_with_limit<T>(
+    input: &T,
+    ignore_garbage: bool,
+    limit: usize,
+) -> Result<Vec<u8>, CompressError>
+where
+    T: ?Sized + AsRef<str>,
+{
+    inner_decode_auto(input.as_ref(), ignore_garbage, Some(limit))
+}
+
+fn inner_decode_auto(
+    input: &str,
+    ignore_garbage: bool,
+    limit: Option<usize>,
+) -> Result<Vec<u8>, CompressError> {
+    let is_tag = |code_point: char| (TAG_START..TAG_END).contains(&(code_point as u32));
+    let start = if ignore_garbage {
+        // the tag is the first character that isn't garbage
+        input
+            .char_indices()
+            .find(|&(_, code_point)| is_tag(code_point) || decode_code_point(code_point).is_some())
+            .map_or(input.len(), |(index, _)| index)
+    } else {
+        0
+    };
+
+    match input[start..].chars().next() {
+        Some(tag) if is_tag(tag) => {
+            let codec = Compression::from_tag(tag).ok_or(CompressError::UnknownCodec(tag))?;
+            let start = start + tag.len_utf8();
+
+            // offsets in errors should be from the start of the whole input
+            let compressed = decode(&input[start..], ignore_garbage).map_err(|err| match err {
+                Error::InvalidCodePoint(offset, code_point) => {
+                    CompressError::Decode(Error::InvalidCodePoint(offset + start, code_point))
+                }
+                err => CompressError::Decode(err),
+            })?;
+            codec.decompress(&compressed, limit)
+        }
+        _ => match decode(input, ignore_garbage) {
+            Ok(output) => match limit {
+                Some(limit) if output.len() > limit => Err(CompressError::TooLarge(limit)),
+                _ => Ok(output),
+            },
+            Err(err) => Err(CompressError::Decode(err)),
+        },
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode_code_point, DecodeResult, Error};
+use std::iter::FusedIterator;
+use std::str::CharIndices;
+
+#[derive(Clone, Debug)]
+/// An iterator over the messages in a concatenated base65536 stream.
+///
+/// Created by [`decode_concatenated_iter`]. See it for examples.
+///
+/// [`decode_concatenated_iter`]: fn.decode_concatenated_iter.html
+pub struct Messages<'a> {
+    input: CharIndices<'a>,
+    ignore_garbage: bool,
+    failed: bool,
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = DecodeResult<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let mut buf = Vec::new();
+        for (index, code_point) in &mut self.input {
+            match decode_code_point(code_point) {
+                Some((byte1, None)) => {
+                    buf.push(byte1);
+                    return Some(Ok(buf));
+                }
+                Some((byte1, Some(byte2))) => {
+                    buf.push(byte1);
+                    buf.push(byte2);
+                }
+                None if self.ignore_garbage => {}
+                None => {
+                    self.failed = true;
+                    return Some(Err(Error::InvalidCodePoint(index, code_point)));
+                }
+            }
+        }
+
+        if buf.is_empty() {
+            None
+        } else {
+            Some(Ok(buf))
+        }
+    }
+}
+
+impl<'a> FusedIterator for Messages<'a> {}
+
+/// Decode a stream of concatenated base65536 messages, lazily.
+///
+/// Each padding character is treated as the end of a message, rather than as
+/// the end of the stream, and whatever follows the final padding character is
+/// returned as the last message. Once an error is returned, the iterator ends.
+///
+/// Note that only messages of an odd length end in a padding character, so
+/// a message of an even length is indistinguishable from the start of the
+/// message after it. Because of this, an even-length message will be merged
+/// with the message that follows.
+///
+/// # Errors
+///
+/// If the input string contains a character not inside of a base65536 block,
+/// [`Error::InvalidCodePoint`] will be returned, along with the bad character,
+/// and its position in the input. Use the `ignore_garbage` option to prevent
+/// this.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::{decode_concatenated_iter, encode};
+///
+/// let log = encode("foo", None) + &encode("bar", None);
+/// let mut messages = decode_concatenated_iter(&log, false);
+/// assert_eq!(b"foo", &messages.next().unwrap()?[..]);
+/// assert_eq!(b"bar", &messages.next().unwrap()?[..]);
+/// assert!(messages.next().is_none());
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+pub fn decode_concatenated_iter<T>(input: &T, ignore_garbage: bool) -> Messages<'_>
+where
+    T: ?Sized + AsRef<str>,
+{
+    Messages {
+        input: input.as_ref().char_indices(),
+        ignore_garbage,
+        failed: false,
+    }
+}
+
+/// Decode a stream of concatenated base65536 messages.
+///
+/// Each padding character is treated as the end of a message, rather than as
+/// the end of the stream, and whatever follows the final padding character is
+/// returned as the last message.
+///
+/// Note that only messages of an odd length end in a padding character, so
+/// a message of an even length is indistinguishable from the start of the
+/// message after it. Because of this, an even-length message will be merged
+/// with the message that follows.
+///
+/// # Errors
+///
+/// If the input string contains a character not inside of a base65536 block,
+/// [`Error::InvalidCodePoint`] will be returned, along with the bad character,
+/// and its position in the input. Use the `ignore_garbage` option to prevent
+/// this.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::{decode_concatenated, encode};
+///
+/// let mut log = String::new();
+/// log += &encode("foo", None);
+/// log += &encode("bar", None);
+///
+/// assert_eq!(vec![b"foo".to_vec(), b"bar".to_vec()], decode_concatenated(&log, false)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+pub fn decode_concatenated<T>(input: &T, ignore_garbage: bool) -> DecodeResult<Vec<Vec<u8>>>
+where
+    T: ?Sized + AsRef<str>,
+{
+    decode_concatenated_iter(input, ignore_garbage).collect()
+}
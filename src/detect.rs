@@ -0,0 +1,206 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::base64;
+use crate::codec::{AnyCodec, Codec};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A format that encoded data may be in.
+///
+/// Returned by [`detect`] and [`decode_any`]. See them for examples.
+///
+/// [`detect`]: fn.detect.html
+/// [`decode_any`]: fn.decode_any.html
+pub enum Format {
+    /// Base65536.
+    Base65536,
+    /// Base32768.
+    Base32768,
+    /// Base2048.
+    Base2048,
+    /// Base16384.
+    Base16384,
+    /// Base64, with either the standard or URL-safe alphabet, and with or
+    /// without padding.
+    Base64,
+    /// Hexadecimal, in either case.
+    Hex,
+}
+
+// Every format, in order of preference when they are equally likely.
+const FORMATS: &[Format] = &[
+    Format::Hex,
+    Format::Base64,
+    Format::Base16384,
+    Format::Base2048,
+    Format::Base32768,
+    Format::Base65536,
+];
+
+impl Format {
+    // The number of distinct characters of the format, counting padding and
+    // final characters.
+    fn alphabet_size(self) -> f64 {
+        match self {
+            Format::Base65536 => 65_536.0 + 256.0,
+            Format::Base32768 => 32_768.0 + 128.0,
+            Format::Base2048 => 2_048.0 + 8.0,
+            Format::Base16384 => 16_384.0 + 6.0,
+            Format::Base64 => 65.0,
+            Format::Hex => 16.0,
+        }
+    }
+
+    fn decode(self, input: &str) -> Option<Vec<u8>> {
+        let codec = match self {
+            Format::Base65536 => AnyCodec::Base65536,
+            Format::Base32768 => AnyCodec::Base32768,
+            Format::Base2048 => AnyCodec::Base2048,
+            Format::Base16384 => AnyCodec::Base16384,
+            Format::Base64 => return base64::decode(input),
+            Format::Hex => return decode_hex(input),
+        };
+        codec.decode(input, false).ok()
+    }
+}
+
+fn decode_hex(input: &str) -> Option<Vec<u8>> {
+    if input.len() % 2 != 0 {
+        return None;
+    }
+
+    let digits: Option<Vec<u8>> = input
+        .chars()
+        .map(|digit| digit.to_digit(16).map(|digit| digit as u8))
+        .collect();
+    Some(
+        digits?
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect(),
+    )
+}
+
+/// Guesses which formats some encoded text may be in, with how likely each
+/// one is.
+///
+/// Whitespace is ignored, so wrapped text can be detected. Every format that
+/// the rest of the text can be fully decoded as is returned, so the
+/// characters must all fall within the blocks or alphabet of the format, and
+/// padding characters must be where the format allows them.
+///
+/// The confidence of each format is between 0 and 1, and they add up to 1.
+/// Formats with fewer characters are more likely, as text that fits within a
+/// small alphabet is unlikely to have been drawn from a larger one, so
+/// hexadecimal is more likely than base64, which is more likely than
+/// base2048, while longer text is more certain. The formats are returned in
+/// order of confidence, highest first. Empty text, or text that fits no
+/// format, returns nothing.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{detect, Format};
+///
+/// let formats = detect("驨ꍬ啯𒁷ꍲᕤ");
+/// assert_eq!(vec![(Format::Base65536, 1.0)], formats);
+///
+/// // this is valid hexadecimal, base64, and base2048
+/// let formats = detect("deadbeef");
+/// let order: Vec<Format> = formats.iter().map(|&(format, _)| format).collect();
+/// assert_eq!(vec![Format::Hex, Format::Base64, Format::Base2048], order);
+/// assert!(formats[0].1 > 0.99);
+/// ```
+pub fn detect<T>(input: &T) -> Vec<(Format, f64)>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let (text, len) = compact(input.as_ref());
+    let candidates: Vec<Format> = FORMATS
+        .iter()
+        .cloned()
+        .filter(|format| len > 0 && format.decode(&text).is_some())
+        .collect();
+    rank(candidates, len)
+}
+
+/// Decode text in whichever format it most likely is, as chosen by
+/// [`detect`].
+///
+/// Returns the format, and the decoded data, or `None` if the text is empty
+/// or fits no format.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{decode_any, Format};
+///
+/// assert_eq!(Some((Format::Base65536, b"hello world".to_vec())), decode_any("驨ꍬ啯\n𒁷ꍲᕤ"));
+/// assert_eq!(Some((Format::Base64, b"hello world".to_vec())), decode_any("aGVsbG8gd29ybGQ="));
+/// assert_eq!(Some((Format::Hex, b"hello".to_vec())), decode_any("68656C6C6F"));
+/// assert_eq!(None, decode_any("hello world!"));
+/// ```
+///
+/// [`detect`]: fn.detect.html
+pub fn decode_any<T>(input: &T) -> Option<(Format, Vec<u8>)>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let (text, len) = compact(input.as_ref());
+    if len == 0 {
+        return None;
+    }
+
+    let mut decoded: Vec<(Format, Vec<u8>)> = FORMATS
+        .iter()
+        .filter_map(|&format| format.decode(&text).map(|data| (format, data)))
+        .collect();
+    let best = rank(decoded.iter().map(|&(format, _)| format).collect(), len)
+        .first()?
+        .0;
+    let index = decoded.iter().position(|&(format, _)| format == best)?;
+    Some(decoded.swap_remove(index))
+}
+
+// The text without whitespace, and how many characters are left.
+fn compact(input: &str) -> (String, usize) {
+    let text: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let len = text.chars().count();
+    (text, len)
+}
+
+// Weighs each format by how likely `len` characters drawn evenly from its
+// alphabet are to be exactly these, relative to the other formats.
+fn rank(candidates: Vec<Format>, len: usize) -> Vec<(Format, f64)> {
+    let log_likelihood = |format: Format| -(len as f64) * format.alphabet_size().ln();
+    let max = candidates
+        .iter()
+        .map(|&format| log_likelihood(format))
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let weights: Vec<f64> = candidates
+        .iter()
+        .map(|&format| (log_likelihood(format) - max).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+
+    let mut ranked: Vec<(Format, f64)> = candidates
+        .into_iter()
+        .zip(weights)
+        .map(|(format, weight)| (format, weight / total))
+        .collect();
+    // stable, so ties keep the order of preference
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    ranked
+}
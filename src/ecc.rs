@@ -0,0 +1,367 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reed–Solomon error correction, for base65536 that passes through lossy
+//! channels like OCR or hand transcription.
+//!
+//! Every base65536 character carries a single 16-bit symbol, so the data is
+//! split into blocks of characters, each of which is a Reed–Solomon codeword
+//! over GF(2^16). Each block starts with its parity characters, followed by
+//! its data characters, exactly as [`encode`] would encode them. The final
+//! block may have fewer data characters than the others.
+//!
+//! A block with `parity` parity characters can be corrected as long as twice
+//! the number of substituted characters, plus the number of erased
+//! characters, is at most `parity`. When decoding, whitespace is ignored,
+//! and any other character that isn't base65536 is taken to be an erasure;
+//! characters can be replaced, but not inserted or removed.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::ecc::{decode, encode, Params};
+//!
+//! let params = Params::new(4, 4)?;
+//! let encoded = encode("hello world", params, None);
+//!
+//! // one character was misread, and another couldn't be read at all
+//! let mut damaged: Vec<char> = encoded.chars().collect();
+//! damaged[5] = '啰';
+//! damaged[10] = '?';
+//! let damaged: String = damaged.into_iter().collect();
+//!
+//! let decoded = decode(&damaged, params)?;
+//! assert_eq!(b"hello world", &decoded.data[..]);
+//! assert_eq!(2, decoded.corrected.len());
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+//!
+//! [`encode`]: ../fn.encode.html
+
+use crate::{decode_code_point, encode_buf, gf, WrapOptions};
+use std::{error, fmt};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// The shape of the blocks that data is split into.
+pub struct Params {
+    data: usize,
+    parity: usize,
+}
+
+impl Params {
+    /// Create parameters for blocks of up to `data` data characters, each
+    /// followed by `parity` parity characters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EccError::InvalidParams`] if either is zero, or if a block
+    /// would be longer than the 65,534 characters a codeword may be.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base65536::ecc::{EccError, Params};
+    ///
+    /// assert!(Params::new(223, 32).is_ok());
+    /// assert_eq!(Err(EccError::InvalidParams), Params::new(0, 32));
+    /// assert_eq!(Err(EccError::InvalidParams), Params::new(65_000, 1_000));
+    /// ```
+    ///
+    /// [`EccError::InvalidParams`]: enum.EccError.html#variant.InvalidParams
+    pub fn new(data: usize, parity: usize) -> Result<Self, EccError> {
+        // the final block's codeword also holds whether it was padded
+        if data == 0 || parity == 0 || data.saturating_add(parity) >= gf::ORDER {
+            Err(EccError::InvalidParams)
+        } else {
+            Ok(Params { data, parity })
+        }
+    }
+
+    /// The most data characters in each block.
+    pub fn data(&self) -> usize {
+        self.data
+    }
+
+    /// The number of parity characters in each block.
+    pub fn parity(&self) -> usize {
+        self.parity
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while using error correction.
+///
+/// Used with [`Params::new`] and [`decode`]. See them for examples.
+///
+/// [`Params::new`]: struct.Params.html#method.new
+/// [`decode`]: fn.decode.html
+pub enum EccError {
+    /// The block shape was invalid.
+    InvalidParams,
+    /// The number of characters didn't fit the block shape, so characters
+    /// were likely inserted or removed.
+    InvalidLength,
+    /// A block had too many errors to be corrected.
+    ///
+    /// Contains the offset from the beginning of the stream at which the
+    /// block starts.
+    Uncorrectable(usize),
+}
+
+impl fmt::Display for EccError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EccError::InvalidParams => write!(f, "invalid block shape"),
+            EccError::InvalidLength => write!(f, "length doesn't fit the block shape"),
+            EccError::Uncorrectable(offset) => {
+                write!(f, "too many errors in block at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl error::Error for EccError {
+    fn description(&self) -> &str {
+        match *self {
+            EccError::InvalidParams => "invalid block shape",
+            EccError::InvalidLength => "invalid length",
+            EccError::Uncorrectable(_) => "too many errors",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+/// Data recovered by error correction.
+///
+/// Returned by [`decode`]. See it for examples.
+///
+/// [`decode`]: fn.decode.html
+pub struct Decoded {
+    /// The decoded data.
+    pub data: Vec<u8>,
+    /// The offset from the beginning of the stream of every character that
+    /// was corrected, including erasures, in order.
+    pub corrected: Vec<usize>,
+}
+
+fn symbol(bytes: &[u8]) -> u16 {
+    u16::from(bytes[0]) | bytes.get(1).map_or(0, |&byte| u16::from(byte) << 8)
+}
+
+/// Encode arbitrary octets as base65536, with error correction.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::ecc::{encode, Params};
+///
+/// let encoded = encode("hello world", Params::new(4, 2).unwrap(), None);
+/// assert_eq!(10, encoded.chars().count());
+/// assert!(encoded.ends_with("ꍲᕤ"));
+/// ```
+pub fn encode<'a, T, W>(input: &T, params: Params, wrap: W) -> String
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    // The codeword of each block is its data symbols followed by its parity.
+    // The final block's codeword begins with a symbol that isn't sent, saying
+    // whether the data ends in a padding character, as otherwise replacing
+    // that character would change the length of the data unnoticed.
+    let input = input.as_ref();
+    let generator = gf::generator(params.parity);
+    let blocks = input.len().div_ceil(params.data * 2);
+
+    let mut bytes = Vec::with_capacity(input.len() + blocks * params.parity * 2);
+    for (index, block) in input.chunks(params.data * 2).enumerate() {
+        let padded = block.len() % 2 == 1;
+        let flag = if index + 1 == blocks {
+            Some(padded as u16)
+        } else {
+            None
+        };
+        let parity = gf::remainder(
+            flag.into_iter().chain(block.chunks(2).map(symbol)),
+            &generator,
+        );
+
+        for symbol in parity {
+            bytes.extend_from_slice(&symbol.to_le_bytes());
+        }
+        bytes.extend_from_slice(block);
+    }
+
+    let mut output = String::with_capacity(bytes.len() * 2);
+    encode_buf(&bytes, &mut output, wrap);
+    output
+}
+
+/// Decode base65536 with error correction, as created by [`encode`],
+/// correcting what errors it can.
+///
+/// `params` must be the same as were used to encode the data. Whitespace is
+/// ignored, so line breaks can be used to wrap the data, and any other
+/// character that isn't base65536 is treated as an erasure.
+///
+/// # Errors
+///
+/// Returns [`EccError::InvalidLength`] if the number of characters couldn't
+/// have been produced by [`encode`], and [`EccError::Uncorrectable`] if a
+/// block had too many errors to correct.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::ecc::{decode, encode, EccError, Params};
+///
+/// let params = Params::new(4, 2)?;
+/// let encoded = encode("hello world", params, None);
+/// let damaged = encoded.replace("啯", "啰");
+///
+/// let decoded = decode(&damaged, params)?;
+/// assert_eq!(b"hello world", &decoded.data[..]);
+/// assert_eq!(damaged.find('啰'), decoded.corrected.first().cloned());
+///
+/// let damaged = damaged.replace("ꍬ", "ꍭ");
+/// assert_eq!(Err(EccError::Uncorrectable(0)), decode(&damaged, params));
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`encode`]: fn.encode.html
+/// [`EccError::InvalidLength`]: enum.EccError.html#variant.InvalidLength
+/// [`EccError::Uncorrectable`]: enum.EccError.html#variant.Uncorrectable
+pub fn decode<T>(input: &T, params: Params) -> Result<Decoded, EccError>
+where
+    T: ?Sized + AsRef<str>,
+{
+    // the offset in the input of every character, and what it decoded to
+    let chars: Vec<_> = input
+        .as_ref()
+        .char_indices()
+        .filter(|&(_, code_point)| !code_point.is_whitespace())
+        .map(|(index, code_point)| (index, decode_code_point(code_point)))
+        .collect();
+
+    let mut decoded = Decoded::default();
+    if chars.is_empty() {
+        return Ok(decoded);
+    }
+
+    // every block but the last is full, and the last has at least one data
+    // character
+    let block_len = params.data + params.parity;
+    let full_blocks = match chars.len().checked_sub(params.parity + 1) {
+        Some(len) if len % block_len < params.data => len / block_len,
+        _ => return Err(EccError::InvalidLength),
+    };
+    decoded.data.reserve(chars.len() * 2);
+
+    for (index, block) in chars.chunks(block_len).enumerate() {
+        let last = index == full_blocks;
+        let start = last as usize;
+        let (parity, data) = block.split_at(params.parity);
+
+        let mut codeword = Vec::with_capacity(start + block.len());
+        let mut erasures = Vec::new();
+        if last {
+            // whether the data was padded, which is filled in below
+            codeword.push(0);
+        }
+        for &(_, bytes) in data.iter().chain(parity) {
+            match bytes {
+                Some((byte1, byte2)) => codeword.push(symbol(&[byte1, byte2.unwrap_or(0)])),
+                None => {
+                    erasures.push(codeword.len());
+                    codeword.push(0);
+                }
+            }
+        }
+
+        let uncorrectable = EccError::Uncorrectable(block[0].0);
+        let (codeword, mut corrected) = if last {
+            // The final character says whether the data was padded, but it
+            // may itself be damaged, so both are tried. Whichever needs
+            // fewer corrections is taken, or what the character says if
+            // both need as many.
+            let padded = data
+                .last()
+                .is_some_and(|&(_, bytes)| bytes.is_some_and(|(_, byte2)| byte2.is_none()));
+            let mut best: Option<(Vec<u16>, Vec<usize>)> = None;
+            for &flag in &[padded, !padded] {
+                let mut attempt = codeword.clone();
+                attempt[0] = flag as u16;
+                let corrected = match gf::correct(&mut attempt, params.parity, &erasures) {
+                    Some(corrected) => corrected,
+                    None => continue,
+                };
+
+                // a padded final symbol holds only a single octet
+                let valid = match attempt[0] {
+                    0 => true,
+                    1 => attempt[data.len()] <= 0xFF,
+                    _ => false,
+                };
+                if valid
+                    && best
+                        .as_ref()
+                        .map_or(true, |best| corrected.len() < best.1.len())
+                {
+                    best = Some((attempt, corrected));
+                }
+            }
+
+            let (codeword, mut corrected) = best.ok_or(uncorrectable)?;
+            if codeword[0] != padded as u16 && !corrected.contains(&data.len()) {
+                // the final character was of the wrong kind, but carried the
+                // right octets
+                corrected.push(data.len());
+            }
+            (codeword, corrected)
+        } else {
+            let corrected =
+                gf::correct(&mut codeword, params.parity, &erasures).ok_or(uncorrectable)?;
+            (codeword, corrected)
+        };
+
+        // map positions in the codeword back to offsets in the input
+        corrected = corrected
+            .into_iter()
+            .filter(|&position| position >= start)
+            .map(|position| match position - start {
+                position if position < data.len() => data[position].0,
+                position => parity[position - data.len()].0,
+            })
+            .collect();
+        corrected.sort();
+        decoded.corrected.extend(corrected);
+
+        let symbols = &codeword[start..start + data.len()];
+        for &symbol in symbols {
+            decoded.data.extend_from_slice(&symbol.to_le_bytes());
+        }
+        if last && codeword[0] == 1 {
+            decoded.data.pop();
+        }
+    }
+
+    Ok(decoded)
+}
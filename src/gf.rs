@@ -0,0 +1,258 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Arithmetic over GF(2^16), in which every base65536 character is a symbol.
+//!
+//! Polynomials are slices of coefficients, highest degree first.
+
+use lazy_static::lazy_static;
+
+/// The number of non-zero elements, which is also the longest possible
+/// Reed–Solomon codeword.
+pub(crate) const ORDER: usize = 65535;
+
+// x^16 + x^12 + x^3 + x + 1, which is primitive, so x generates the field
+const PRIMITIVE: u32 = 0x1_100B;
+
+lazy_static! {
+    static ref TABLES: (Vec<u16>, Vec<u16>) = {
+        // exp is doubled, so that the sum of two logarithms needn't be reduced
+        let mut exp = vec![0; ORDER * 2];
+        let mut log = vec![0; ORDER + 1];
+
+        let mut x: u32 = 1;
+        for i in 0..ORDER {
+            exp[i] = x as u16;
+            exp[i + ORDER] = x as u16;
+            log[x as usize] = i as u16;
+
+            x <<= 1;
+            if x & 0x1_0000 != 0 {
+                x ^= PRIMITIVE;
+            }
+        }
+
+        (exp, log)
+    };
+}
+
+#[inline]
+fn exp_table() -> &'static [u16] {
+    &TABLES.0
+}
+
+#[inline]
+fn log_table() -> &'static [u16] {
+    &TABLES.1
+}
+
+/// Returns α^power, where α is the generator of the field.
+#[inline]
+pub(crate) fn exp(power: usize) -> u16 {
+    exp_table()[power % ORDER]
+}
+
+/// Returns the power of α that is `x`, which must not be zero.
+#[inline]
+pub(crate) fn log(x: u16) -> usize {
+    debug_assert_ne!(x, 0);
+    usize::from(log_table()[usize::from(x)])
+}
+
+#[inline]
+pub(crate) fn mul(x: u16, y: u16) -> u16 {
+    if x == 0 || y == 0 {
+        0
+    } else {
+        exp_table()[log(x) + log(y)]
+    }
+}
+
+/// Divides `x` by `y`, which must not be zero.
+#[inline]
+pub(crate) fn div(x: u16, y: u16) -> u16 {
+    if x == 0 {
+        0
+    } else {
+        exp_table()[log(x) + ORDER - log(y)]
+    }
+}
+
+/// Evaluates a polynomial at `x`.
+pub(crate) fn eval(poly: &[u16], x: u16) -> u16 {
+    poly.iter()
+        .fold(0, |acc, &coefficient| mul(acc, x) ^ coefficient)
+}
+
+/// Returns the Reed–Solomon generator polynomial with the roots
+/// α^1, α^2, ..., α^`nsym`.
+pub(crate) fn generator(nsym: usize) -> Vec<u16> {
+    let mut poly = vec![1];
+    for i in 1..=nsym {
+        // multiply by (x - α^i), remembering that subtraction is addition
+        let root = exp(i);
+        let mut next = poly.clone();
+        next.push(0);
+        for (j, &coefficient) in poly.iter().enumerate() {
+            next[j + 1] ^= mul(coefficient, root);
+        }
+        poly = next;
+    }
+    poly
+}
+
+/// Returns the remainder of dividing `message` * x^(degree of `generator`)
+/// by `generator`, which must be monic. Appending this to the message gives
+/// a systematic Reed–Solomon codeword.
+pub(crate) fn remainder<I>(message: I, generator: &[u16]) -> Vec<u16>
+where
+    I: IntoIterator<Item = u16>,
+{
+    let mut remainder = vec![0; generator.len() - 1];
+    for symbol in message {
+        let factor = symbol ^ remainder[0];
+        remainder.remove(0);
+        remainder.push(0);
+        if factor != 0 {
+            for (r, &g) in remainder.iter_mut().zip(&generator[1..]) {
+                *r ^= mul(g, factor);
+            }
+        }
+    }
+    remainder
+}
+
+/// Returns the syndromes of a codeword, evaluated at α^1, ..., α^`nsym`.
+pub(crate) fn syndromes(codeword: &[u16], nsym: usize) -> Vec<u16> {
+    (1..=nsym).map(|i| eval(codeword, exp(i))).collect()
+}
+
+// Evaluates a polynomial stored lowest degree first, as locators are.
+fn eval_reversed(poly: &[u16], x: u16) -> u16 {
+    poly.iter()
+        .rev()
+        .fold(0, |acc, &coefficient| mul(acc, x) ^ coefficient)
+}
+
+/// Corrects a Reed–Solomon codeword with `nsym` check symbols in place,
+/// given the positions of any symbols known to be wrong.
+///
+/// Up to `nsym` errata can be corrected, where an unknown error counts
+/// twice and an erasure once. Returns the positions of every corrected
+/// symbol, in order, or `None` if there were too many errata.
+pub(crate) fn correct(codeword: &mut [u16], nsym: usize, erasures: &[usize]) -> Option<Vec<usize>> {
+    let len = codeword.len();
+    debug_assert!(len <= ORDER);
+    if erasures.len() > nsym {
+        return None;
+    }
+
+    let syndromes = syndromes(codeword, nsym);
+    if syndromes.iter().all(|&syndrome| syndrome == 0) {
+        return Some(Vec::new());
+    }
+
+    // the symbol at position i is the coefficient of x^(len - 1 - i), and is
+    // located by α to that power
+    let locator = |position: usize| exp(len - 1 - position);
+
+    // The errata locator, lowest degree first, is found by Berlekamp–Massey,
+    // starting from the product of (1 - X·x) for every erasure.
+    let mut lambda = vec![1];
+    for &position in erasures {
+        let x = locator(position);
+        let mut next = lambda.clone();
+        next.push(0);
+        for (j, &coefficient) in lambda.iter().enumerate() {
+            next[j + 1] ^= mul(coefficient, x);
+        }
+        lambda = next;
+    }
+    let mut previous = lambda.clone();
+    let mut errata = erasures.len();
+    for r in erasures.len() + 1..=nsym {
+        let delta = lambda
+            .iter()
+            .enumerate()
+            .take_while(|&(j, _)| j < r)
+            .fold(0, |acc, (j, &coefficient)| {
+                acc ^ mul(coefficient, syndromes[r - j - 1])
+            });
+
+        previous.insert(0, 0);
+        if delta != 0 {
+            let mut next = lambda.clone();
+            next.resize(next.len().max(previous.len()), 0);
+            for (n, &p) in next.iter_mut().zip(&previous) {
+                *n ^= mul(p, delta);
+            }
+            if 2 * errata < r + erasures.len() {
+                previous = lambda
+                    .iter()
+                    .map(|&coefficient| div(coefficient, delta))
+                    .collect();
+                errata = r + erasures.len() - errata;
+            }
+            lambda = next;
+        }
+    }
+    while lambda.last() == Some(&0) {
+        lambda.pop();
+    }
+    let degree = lambda.len() - 1;
+    if degree != errata || 2 * errata - erasures.len() > nsym {
+        return None;
+    }
+
+    // the errata are where the inverse of their locator is a root
+    let positions: Vec<usize> = (0..len)
+        .filter(|&position| eval_reversed(&lambda, div(1, locator(position))) == 0)
+        .collect();
+    if positions.len() != degree {
+        return None;
+    }
+
+    // Forney's algorithm gives each error's magnitude from the evaluator
+    // S(x)·Λ(x) mod x^nsym and the formal derivative of Λ.
+    let mut omega = vec![0; nsym];
+    for (i, &s) in syndromes.iter().enumerate() {
+        for (j, &l) in lambda.iter().enumerate().take(nsym - i) {
+            omega[i + j] ^= mul(s, l);
+        }
+    }
+    let derivative: Vec<u16> = lambda
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(j, &coefficient)| if j % 2 == 1 { coefficient } else { 0 })
+        .collect();
+    for &position in &positions {
+        let inverse = div(1, locator(position));
+        let denominator = eval_reversed(&derivative, inverse);
+        if denominator == 0 {
+            return None;
+        }
+        codeword[position] ^= div(eval_reversed(&omega, inverse), denominator);
+    }
+
+    if syndromes_are_zero(codeword, nsym) {
+        Some(positions)
+    } else {
+        None
+    }
+}
+
+fn syndromes_are_zero(codeword: &[u16], nsym: usize) -> bool {
+    (1..=nsym).all(|i| eval(codeword, exp(i)) == 0)
+}
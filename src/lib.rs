@@ -50,7 +50,10 @@
 //!
 //! // word wrapping with a custom line ending
 //! assert_eq!("㘁\r\nᔃ", encode(&[1, 2, 3], WrapOptions::WrapAtWith(1, "\r\n")));
-//! assert_eq!("驨ꍬ啯\r\n𒁷ꍲᕤ", encode("hello world", WrapOptions::WrapAtWith(3, "\r\n")));
+//! assert_eq!(
+//!     "驨ꍬ啯\r\n𒁷ꍲᕤ",
+//!     encode("hello world", WrapOptions::WrapAtWith(3, "\r\n"))
+//! );
 //! ```
 //!
 //! [qntm]: https://qntm.org/
@@ -63,6 +66,53 @@ extern crate test as test_crate;
 #[cfg(test)]
 mod test;
 
+pub mod alphabet;
+pub mod armor;
+pub mod base16384;
+pub mod base2048;
+pub mod base32768;
+pub mod codec;
+pub mod ecc;
+pub mod ordered;
+pub mod twitter;
+
+mod base64;
+mod budget;
+mod checked;
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+mod compress;
+mod concat;
+mod detect;
+mod gf;
+mod lines;
+mod lossy;
+mod packed;
+mod report;
+mod split;
+mod transcode;
+mod width;
+mod words;
+mod wrapped;
+
+pub use crate::budget::{encode_prefix_within, Budget};
+pub use crate::checked::{decode_checked, encode_checked, CheckedError, MAX_CHECKED_LEN};
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+pub use crate::compress::{
+    decode_auto, decode_auto_with_limit, encode_compressed, CompressError, Compression,
+};
+pub use crate::concat::{decode_concatenated, decode_concatenated_iter, Messages};
+pub use crate::detect::{decode_any, detect, Format};
+pub use crate::lines::{LineDecoder, LineError};
+pub use crate::lossy::decode_lossy;
+pub use crate::report::{decode_with_report, DecodeReport, SkippedSpan};
+pub use crate::split::{split_encode, Reassembler, SplitError};
+pub use crate::transcode::{
+    transcode_from_base64, transcode_from_base64_io, transcode_to_base64, transcode_to_base64_io,
+    TranscodeError,
+};
+pub use crate::words::{decode_to_u16s, encode_u16s, Endian};
+pub use crate::wrapped::{decode_wrapped, detect_wrap};
+
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::{error, fmt};
@@ -74,7 +124,7 @@ use fnv::FnvBuildHasher as Hasher;
 use std::collections::hash_map::RandomState as Hasher;
 
 const PADDING_BLOCK_START: u32 = 0x1500;
-#[cfg_attr(feature = "cargo-clippy", allow(unreadable_literal))]
+#[allow(clippy::unreadable_literal)]
 const BLOCK_STARTS: &[u32] = &[
     0x03400, 0x03500, 0x03600, 0x03700, 0x03800, 0x03900, 0x03A00, 0x03B00, 0x03C00, 0x03D00,
     0x03E00, 0x03F00, 0x04000, 0x04100, 0x04200, 0x04300, 0x04400, 0x04500, 0x04600, 0x04700,
@@ -103,6 +153,19 @@ const BLOCK_STARTS: &[u32] = &[
     0x27600, 0x27700, 0x27800, 0x27900, 0x27A00, 0x27B00, 0x27C00, 0x27D00, 0x27E00, 0x27F00,
     0x28000, 0x28100, 0x28200, 0x28300, 0x28400, 0x28500,
 ];
+// The number of terminal cells taken up by the characters of the padding block,
+// and of each block in BLOCK_STARTS, going by their East Asian Width.
+const PADDING_DISPLAY_WIDTH: usize = 1;
+const BLOCK_DISPLAY_WIDTHS: &[u8] = &[
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+];
 lazy_static! {
     static ref BLOCK_START_TO_INDEX: HashMap<u32, u8, Hasher> = (0..BLOCK_STARTS.len())
         .map(|b| (BLOCK_STARTS[b], b as u8))
@@ -152,36 +215,92 @@ impl error::Error for Error {
 /// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
 pub type DecodeResult<T> = ::std::result::Result<T, Error>;
 
+/// Decodes a single base65536 character into its one or two octets.
+///
+/// A `None` as the second octet means that this is a padding character.
+/// Returns `None` if the character isn't part of base65536 at all.
+#[inline]
+fn decode_code_point(code_point: char) -> Option<(u8, Option<u8>)> {
+    let (byte1, block_start) = {
+        const BLOCK_MASK: u32 = (1 << 8) - 1;
+        let code_point = code_point as u32;
+
+        let byte1 = code_point & BLOCK_MASK;
+        (byte1 as u8, code_point - byte1)
+    };
+
+    if block_start == PADDING_BLOCK_START {
+        Some((byte1, None))
+    } else {
+        BLOCK_START_TO_INDEX
+            .get(&block_start)
+            .map(|byte2| (byte1, Some(*byte2)))
+    }
+}
+
+/// Encodes one or two octets as a single base65536 character.
+///
+/// The second octet selects the block, or the padding block if it's `None`.
 #[inline]
-fn inner_decode<F>(input: &str, ignore_garbage: bool, mut out: F) -> DecodeResult<()>
+fn encode_code_point(byte1: u8, byte2: Option<u8>) -> char {
+    let block_start = match byte2 {
+        None => PADDING_BLOCK_START,
+        Some(byte2) => BLOCK_STARTS[byte2 as usize],
+    };
+
+    // It is safe to unwrap because we know that all code points within
+    // 0x100 of any possible block_start are defined, and that's the
+    // largest possible addition to block_start.
+    let code_point = block_start + u32::from(byte1);
+    std::char::from_u32(code_point).unwrap()
+}
+
+#[inline]
+fn inner_decode<F>(input: &str, ignore_garbage: bool, out: F) -> DecodeResult<()>
 where
     F: FnMut(u8, Option<u8>),
 {
-    let mut done = false;
-    for (index, code_point) in input.char_indices() {
-        let (byte1, block_start) = {
-            const BLOCK_MASK: u32 = (1 << 8) - 1;
-            let code_point = code_point as u32;
+    inner_decode_with_garbage(input, ignore_garbage, out, |_, _| {})
+}
 
-            let byte1 = code_point & BLOCK_MASK;
-            (byte1 as u8, code_point - byte1)
-        };
+#[inline]
+fn inner_decode_with_garbage<F, G>(
+    input: &str,
+    ignore_garbage: bool,
+    out: F,
+    garbage: G,
+) -> DecodeResult<()>
+where
+    F: FnMut(u8, Option<u8>),
+    G: FnMut(usize, char),
+{
+    inner_decode_with(input, ignore_garbage, decode_code_point, out, garbage)
+}
 
-        if block_start == PADDING_BLOCK_START {
-            if done {
-                return Err(Error::InvalidLength);
-            } else {
-                out(byte1, None);
-                done = true;
-            }
-        } else if let Some(byte2) = BLOCK_START_TO_INDEX.get(&block_start) {
-            if done {
-                return Err(Error::InvalidLength);
-            } else {
-                out(byte1, Some(*byte2));
+/// Decodes with any set of blocks, given how to decode a single character.
+#[inline]
+fn inner_decode_with<D, F, G>(
+    input: &str,
+    ignore_garbage: bool,
+    decode_code_point: D,
+    mut out: F,
+    mut garbage: G,
+) -> DecodeResult<()>
+where
+    D: Fn(char) -> Option<(u8, Option<u8>)>,
+    F: FnMut(u8, Option<u8>),
+    G: FnMut(usize, char),
+{
+    let mut done = false;
+    for (index, code_point) in input.char_indices() {
+        match decode_code_point(code_point) {
+            Some(_) if done => return Err(Error::InvalidLength),
+            Some((byte1, byte2)) => {
+                out(byte1, byte2);
+                done = byte2.is_none();
             }
-        } else if !ignore_garbage {
-            return Err(Error::InvalidCodePoint(index, code_point));
+            None if ignore_garbage => garbage(index, code_point),
+            None => return Err(Error::InvalidCodePoint(index, code_point)),
         }
     }
 
@@ -352,6 +471,7 @@ where
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 /// Line Wrapping Options.
 ///
 /// Used with [`encode`] and [`encode_buf`]. See them for examples.
@@ -360,6 +480,9 @@ where
 /// [`Option::None`] instead for no wrapping or an [`usize`] instead for
 /// wrapping at a column boundary, and everything will magically work.
 ///
+/// More ways of wrapping may be added in future, so matching on this needs a
+/// wildcard arm.
+///
 /// [`encode`]: fn.encode.html
 /// [`encode_buf`]: fn.encode_buf.html
 /// [`Option::None`]: https://doc.rust-lang.org/std/option/enum.Option.html#variant.None
@@ -371,6 +494,160 @@ pub enum WrapOptions<'a> {
     WrapAt(usize),
     /// Wrap every so many columns with a specified string. The length must be > 0.
     WrapAtWith(usize, &'a str),
+    /// Wrap with '\n' so that each line takes up at most so many terminal
+    /// cells, rather than so many characters. The length must be > 0.
+    ///
+    /// Most base65536 characters are East Asian Wide, and so take up two
+    /// cells, but some take up only one. A character wider than a whole line
+    /// is put on a line by itself.
+    WrapAtDisplayWidth(usize),
+    /// Wrap with '\n' so that each line is at most so many bytes long when
+    /// encoded as UTF-8. The length must be > 0.
+    ///
+    /// Characters in the blocks from U+20000 take up four bytes, while all
+    /// others take up three. A character longer than a whole line is put on a
+    /// line by itself.
+    WrapAtUtf8Len(usize),
+    /// Wrap with '\n' so that each line is at most so many code units long
+    /// when encoded as UTF-16. The length must be > 0.
+    ///
+    /// Characters in the blocks from U+10000 take up two code units, while all
+    /// others take up one. A character longer than a whole line is put on a
+    /// line by itself.
+    WrapAtUtf16Len(usize),
+    /// Wrap with a validated [`LineFormat`], which may also prefix each line.
+    ///
+    /// [`LineFormat`]: struct.LineFormat.html
+    Formatted(LineFormat<'a>),
+}
+
+impl<'a> WrapOptions<'a> {
+    /// Wrap every so many columns with a specified string, checking that the
+    /// options are valid first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WrapError::ZeroColumns`] if `columns` is 0, and
+    /// [`WrapError::InvalidEol`] if `eol` contains a base65536 character.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn test() -> Result<(), Box<std::error::Error>> {
+    /// use base65536::{encode, WrapError, WrapOptions};
+    ///
+    /// let wrap = WrapOptions::new(3, "\n")?.with_prefix("// ")?.with_trailing_eol()?;
+    /// assert_eq!("// 驨ꍬ啯\n// 𒁷ꍲᕤ\n", encode("hello world", wrap));
+    ///
+    /// assert_eq!(Err(WrapError::ZeroColumns), WrapOptions::new(0, "\n"));
+    /// assert_eq!(Err(WrapError::InvalidEol('㘁')), WrapOptions::new(3, "㘁"));
+    /// # Ok(()) }
+    /// # test().unwrap();
+    /// ```
+    ///
+    /// [`WrapError::ZeroColumns`]: enum.WrapError.html#variant.ZeroColumns
+    /// [`WrapError::InvalidEol`]: enum.WrapError.html#variant.InvalidEol
+    pub fn new(columns: usize, eol: &'a str) -> Result<Self, WrapError> {
+        WrapOptions::WrapAtWith(columns, eol)
+            .line_format()
+            .validate()
+            .map(WrapOptions::Formatted)
+    }
+
+    /// Separate lines with a specified string, rather than '\n', while keeping
+    /// the rest of the options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WrapError::InvalidEol`] if `eol` contains a base65536
+    /// character. As the options are validated as a whole, this may also
+    /// return any of the errors of [`WrapOptions::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn test() -> Result<(), Box<std::error::Error>> {
+    /// use base65536::{encode, WrapOptions};
+    ///
+    /// // SMTP limits lines to 998 bytes, excluding the line ending
+    /// let wrap = WrapOptions::WrapAtUtf8Len(998).with_eol("\r\n")?;
+    /// let encoded = encode(&[0; 4096][..], wrap);
+    /// assert!(encoded.split("\r\n").all(|line| line.len() <= 998));
+    /// # Ok(()) }
+    /// # test().unwrap();
+    /// ```
+    ///
+    /// [`WrapError::InvalidEol`]: enum.WrapError.html#variant.InvalidEol
+    /// [`WrapOptions::new`]: enum.WrapOptions.html#method.new
+    pub fn with_eol(self, eol: &'a str) -> Result<Self, WrapError> {
+        LineFormat {
+            eol,
+            ..self.line_format()
+        }
+        .validate()
+        .map(WrapOptions::Formatted)
+    }
+
+    /// Start every line with a specified string, such as `"// "` or `"> "`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WrapError::InvalidPrefix`] if `prefix` contains a base65536
+    /// character. As the options are validated as a whole, this may also
+    /// return any of the errors of [`WrapOptions::new`].
+    ///
+    /// [`WrapError::InvalidPrefix`]: enum.WrapError.html#variant.InvalidPrefix
+    /// [`WrapOptions::new`]: enum.WrapOptions.html#method.new
+    pub fn with_prefix(self, prefix: &'a str) -> Result<Self, WrapError> {
+        LineFormat {
+            prefix,
+            ..self.line_format()
+        }
+        .validate()
+        .map(WrapOptions::Formatted)
+    }
+
+    /// End the last line with the end-of-line string, as well as every line
+    /// before it.
+    ///
+    /// This does nothing to empty output, which has no lines at all.
+    ///
+    /// # Errors
+    ///
+    /// As the options are validated as a whole, this may return any of the
+    /// errors of [`WrapOptions::new`].
+    ///
+    /// [`WrapOptions::new`]: enum.WrapOptions.html#method.new
+    pub fn with_trailing_eol(self) -> Result<Self, WrapError> {
+        LineFormat {
+            trailing_eol: true,
+            ..self.line_format()
+        }
+        .validate()
+        .map(WrapOptions::Formatted)
+    }
+
+    fn line_format(self) -> LineFormat<'a> {
+        let (columns, eol, measure) = match self {
+            WrapOptions::NoWrap => (None, "\n", Measure::CodePoints),
+            WrapOptions::WrapAt(columns) => (Some(columns), "\n", Measure::CodePoints),
+            WrapOptions::WrapAtWith(columns, eol) => (Some(columns), eol, Measure::CodePoints),
+            WrapOptions::WrapAtDisplayWidth(columns) => {
+                (Some(columns), "\n", Measure::DisplayWidth)
+            }
+            WrapOptions::WrapAtUtf8Len(columns) => (Some(columns), "\n", Measure::Utf8),
+            WrapOptions::WrapAtUtf16Len(columns) => (Some(columns), "\n", Measure::Utf16),
+            WrapOptions::Formatted(format) => return format,
+        };
+
+        LineFormat {
+            columns,
+            eol,
+            prefix: "",
+            trailing_eol: false,
+            measure,
+        }
+    }
 }
 
 impl<'a, T> From<T> for WrapOptions<'a>
@@ -385,6 +662,167 @@ where
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A validated line format.
+///
+/// Created by [`WrapOptions::new`] and the other methods of [`WrapOptions`],
+/// and used through [`WrapOptions::Formatted`].
+///
+/// [`WrapOptions`]: enum.WrapOptions.html
+/// [`WrapOptions::new`]: enum.WrapOptions.html#method.new
+/// [`WrapOptions::Formatted`]: enum.WrapOptions.html#variant.Formatted
+pub struct LineFormat<'a> {
+    columns: Option<usize>,
+    eol: &'a str,
+    prefix: &'a str,
+    trailing_eol: bool,
+    measure: Measure,
+}
+
+impl<'a> LineFormat<'a> {
+    /// The maximum length of a line, if lines are wrapped.
+    ///
+    /// This is in whatever unit the options were created with, which is
+    /// characters unless otherwise specified. Neither the prefix nor the
+    /// end-of-line string count towards it.
+    pub fn columns(&self) -> Option<usize> {
+        self.columns
+    }
+
+    /// The string separating lines.
+    pub fn eol(&self) -> &'a str {
+        self.eol
+    }
+
+    /// The string at the start of every line.
+    pub fn prefix(&self) -> &'a str {
+        self.prefix
+    }
+
+    /// Whether the last line is also followed by the end-of-line string.
+    pub fn trailing_eol(&self) -> bool {
+        self.trailing_eol
+    }
+
+    fn validate(self) -> Result<Self, WrapError> {
+        let is_base65536 = |code_point: &char| decode_code_point(*code_point).is_some();
+
+        if self.columns == Some(0) {
+            Err(WrapError::ZeroColumns)
+        } else if let Some(code_point) = self.eol.chars().find(is_base65536) {
+            Err(WrapError::InvalidEol(code_point))
+        } else if let Some(code_point) = self.prefix.chars().find(is_base65536) {
+            Err(WrapError::InvalidPrefix(code_point))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Measure {
+    CodePoints,
+    DisplayWidth,
+    Utf8,
+    Utf16,
+}
+
+impl Measure {
+    // The width of a single character, given the index of its block in
+    // BLOCK_STARTS, or None if it's in the padding block.
+    #[inline]
+    fn width(self, block: Option<u8>) -> usize {
+        match self {
+            Measure::CodePoints => 1,
+            Measure::DisplayWidth => block.map_or(PADDING_DISPLAY_WIDTH, |block| {
+                usize::from(BLOCK_DISPLAY_WIDTHS[usize::from(block)])
+            }),
+            Measure::Utf8 => match block {
+                Some(block) if BLOCK_STARTS[usize::from(block)] > 0xFFFF => 4,
+                _ => 3,
+            },
+            Measure::Utf16 => match block {
+                Some(block) if BLOCK_STARTS[usize::from(block)] > 0xFFFF => 2,
+                _ => 1,
+            },
+        }
+    }
+
+    // The width of any character, given the number of terminal cells it
+    // takes up, which is only computed if needed.
+    #[inline]
+    fn char_width<F>(self, code_point: char, display_width: F) -> usize
+    where
+        F: FnOnce(char) -> usize,
+    {
+        match self {
+            Measure::CodePoints => 1,
+            Measure::DisplayWidth => display_width(code_point),
+            Measure::Utf8 => code_point.len_utf8(),
+            Measure::Utf16 => code_point.len_utf16(),
+        }
+    }
+
+    // The width of the widest character in any block.
+    fn max_width(self) -> usize {
+        match self {
+            Measure::CodePoints => 1,
+            Measure::DisplayWidth => 2,
+            Measure::Utf8 => 4,
+            Measure::Utf16 => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Represents an invalid set of line wrapping options.
+///
+/// Returned by [`WrapOptions::new`], [`WrapOptions::with_eol`],
+/// [`WrapOptions::with_prefix`] and [`WrapOptions::with_trailing_eol`].
+///
+/// [`WrapOptions::new`]: enum.WrapOptions.html#method.new
+/// [`WrapOptions::with_eol`]: enum.WrapOptions.html#method.with_eol
+/// [`WrapOptions::with_prefix`]: enum.WrapOptions.html#method.with_prefix
+/// [`WrapOptions::with_trailing_eol`]: enum.WrapOptions.html#method.with_trailing_eol
+pub enum WrapError {
+    /// Lines can't be wrapped every 0 columns.
+    ZeroColumns,
+    /// The end-of-line string contains a base65536 character, which would be
+    /// mistaken for data.
+    ///
+    /// Contains the offending character.
+    InvalidEol(char),
+    /// The line prefix contains a base65536 character, which would be
+    /// mistaken for data.
+    ///
+    /// Contains the offending character.
+    InvalidPrefix(char),
+}
+
+impl fmt::Display for WrapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WrapError::ZeroColumns => write!(f, "cannot wrap every 0 columns"),
+            WrapError::InvalidEol(ch) => {
+                write!(f, "end-of-line contains base65536 character '{}'", ch)
+            }
+            WrapError::InvalidPrefix(ch) => {
+                write!(f, "line prefix contains base65536 character '{}'", ch)
+            }
+        }
+    }
+}
+
+impl error::Error for WrapError {
+    fn description(&self) -> &str {
+        match *self {
+            WrapError::ZeroColumns => "zero columns",
+            WrapError::InvalidEol(_) => "invalid end-of-line",
+            WrapError::InvalidPrefix(_) => "invalid line prefix",
+        }
+    }
+}
+
 /// Encode arbitrary octets as base65536.
 ///
 /// The `wrap` option allows wrapping the output every so many characters,
@@ -547,32 +985,49 @@ where
     T: ?Sized + AsRef<[u8]>,
     W: Into<WrapOptions<'a>>,
 {
-    let wrap = wrap.into();
-    for (count, bytes) in input.as_ref().chunks(2).enumerate() {
-        match wrap {
-            WrapOptions::NoWrap => {}
-            WrapOptions::WrapAt(columns) => {
-                if count % columns == 0 && count != 0 {
-                    buf.push('\n');
-                }
-            }
-            WrapOptions::WrapAtWith(columns, eol) => {
-                if count % columns == 0 && count != 0 {
-                    buf.push_str(eol);
-                }
-            }
+    let format = wrap.into().line_format();
+    let chars = input.as_ref().chunks(2).map(|bytes| {
+        let block = bytes.get(1).cloned();
+        (
+            encode_code_point(bytes[0], block),
+            format.measure.width(block),
+        )
+    });
+    write_lines(chars, buf, format);
+}
+
+/// Writes each character to `buf`, broken into lines as set by `format`,
+/// given the width of each character in the unit `format` measures lines in.
+///
+/// Shared by every encoding, so that they all wrap in exactly the same way.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+fn write_lines<I>(chars: I, buf: &mut String, format: LineFormat)
+where
+    I: IntoIterator<Item = (char, usize)>,
+{
+    let columns = format.columns.unwrap_or(usize::MAX);
+    assert!(columns > 0, "cannot wrap every 0 columns");
+
+    let mut column = 0;
+    let mut empty = true;
+    for (code_point, width) in chars {
+        if empty {
+            buf.push_str(format.prefix);
+            empty = false;
+        } else if column + width > columns {
+            buf.push_str(format.eol);
+            buf.push_str(format.prefix);
+            column = 0;
         }
+        column += width;
 
-        let block_start = match bytes.len() {
-            1 => PADDING_BLOCK_START,
-            2 => BLOCK_STARTS[bytes[1] as usize],
-            _ => unreachable!(),
-        };
+        buf.push(code_point);
+    }
 
-        // It is safe to unwrap because we know that all code points within
-        // 0x100 of any possible block_start are defined, and that's the
-        // largest possible addition to block_start.
-        let code_point = block_start + u32::from(bytes[0]);
-        buf.push(std::char::from_u32(code_point).unwrap());
+    if format.trailing_eol && !empty {
+        buf.push_str(format.eol);
     }
 }
@@ -0,0 +1,165 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode, Error};
+use std::io::{self, BufRead};
+use std::{error, fmt, str};
+
+#[derive(Debug)]
+/// Represents an error while decoding a single line.
+///
+/// Used with [`LineDecoder`]. See it for examples.
+///
+/// Every variant contains the number of the line on which the error occurred,
+/// counting from one.
+///
+/// [`LineDecoder`]: struct.LineDecoder.html
+pub enum LineError {
+    /// Reading from the underlying reader failed. No further lines will be
+    /// read after this error.
+    Io(usize, io::Error),
+    /// The line was not valid UTF-8.
+    InvalidUtf8(usize),
+    /// The line was not valid base65536. Offsets within the contained error
+    /// are relative to the start of the line.
+    Decode(usize, Error),
+}
+
+impl LineError {
+    /// The number of the line on which the error occurred, counting from one.
+    pub fn line(&self) -> usize {
+        match *self {
+            LineError::Io(line, _) | LineError::InvalidUtf8(line) | LineError::Decode(line, _) => {
+                line
+            }
+        }
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LineError::Io(line, ref err) => write!(f, "line {}: {}", line, err),
+            LineError::InvalidUtf8(line) => write!(f, "line {}: invalid utf-8", line),
+            LineError::Decode(line, ref err) => write!(f, "line {}: {}", line, err),
+        }
+    }
+}
+
+impl error::Error for LineError {
+    fn description(&self) -> &str {
+        match *self {
+            LineError::Io(_, _) => "i/o error",
+            LineError::InvalidUtf8(_) => "invalid utf-8",
+            LineError::Decode(_, _) => "invalid base65536",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            LineError::Io(_, ref err) => Some(err),
+            LineError::InvalidUtf8(_) => None,
+            LineError::Decode(_, ref err) => Some(err),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// An iterator decoding each line of a reader as a separate base65536 message.
+///
+/// Each line is decoded strictly, as if with [`decode`] without the
+/// `ignore_garbage` option, except that the line ending (either `"\n"` or
+/// `"\r\n"`) is removed first. A final line ending in `"\r"` with no newline
+/// is treated the same way. An empty line is decoded as an empty message.
+///
+/// An invalid line produces a [`LineError`], but the lines after it are still
+/// decoded. Only an I/O error from the underlying reader ends the iteration.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{Error, LineDecoder, LineError};
+///
+/// let log = "驨ꍬ啯𒁷ꍲᕤ\r\n㘁!ᔃ\n㘁ᔃ\n";
+/// let mut lines = LineDecoder::new(log.as_bytes());
+///
+/// assert_eq!(b"hello world", &lines.next().unwrap().unwrap()[..]);
+/// match lines.next().unwrap() {
+///     Err(LineError::Decode(2, Error::InvalidCodePoint(3, '!'))) => {}
+///     _ => panic!(),
+/// }
+/// assert_eq!(vec![1, 2, 3], lines.next().unwrap().unwrap());
+/// assert!(lines.next().is_none());
+/// ```
+///
+/// [`decode`]: fn.decode.html
+/// [`LineError`]: enum.LineError.html
+pub struct LineDecoder<R> {
+    reader: R,
+    buf: Vec<u8>,
+    line: usize,
+    failed: bool,
+}
+
+impl<R: BufRead> LineDecoder<R> {
+    /// Creates a new `LineDecoder` reading from the supplied reader.
+    pub fn new(reader: R) -> Self {
+        LineDecoder {
+            reader,
+            buf: Vec::new(),
+            line: 0,
+            failed: false,
+        }
+    }
+
+    /// Unwraps this `LineDecoder`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for LineDecoder<R> {
+    type Item = Result<Vec<u8>, LineError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        self.buf.clear();
+        self.line += 1;
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(err) => {
+                self.failed = true;
+                return Some(Err(LineError::Io(self.line, err)));
+            }
+        }
+
+        let mut line = &self.buf[..];
+        if line.ends_with(b"\n") {
+            line = &line[..line.len() - 1];
+        }
+        // also tolerated on a final line with no newline
+        if line.ends_with(b"\r") {
+            line = &line[..line.len() - 1];
+        }
+
+        Some(match str::from_utf8(line) {
+            Ok(line) => decode(line, false).map_err(|err| LineError::Decode(self.line, err)),
+            Err(_) => Err(LineError::InvalidUtf8(self.line)),
+        })
+    }
+}
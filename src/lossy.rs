@@ -0,0 +1,77 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode_code_point, Error};
+
+/// Decode from a reference to a base65536-encoded string as octets, recovering
+/// from every error instead of failing.
+///
+/// Every valid base65536 character in the input is decoded. Characters that
+/// aren't part of base65536 are skipped, and a stream that continues after a
+/// terminating padding character simply continues to be decoded.
+///
+/// Along with the decoded octets, this returns every error encountered, in
+/// order, each paired with the offset into the *output* at which it occurred.
+/// An [`Error::InvalidCodePoint`] still contains the offset into the input
+/// at which the bad character was found. An [`Error::InvalidLength`] is
+/// reported once for each padding character that the stream continued after,
+/// at the point at which it continued.
+///
+/// This is intended for recovering as much data as possible from damaged
+/// input. Use [`decode`] if you need to know that the input was valid.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{decode_lossy, Error};
+///
+/// let (buf, errors) = decode_lossy("㘁ᔃ!㘁ᔃ");
+/// assert_eq!(vec![1, 2, 3, 1, 2, 3], buf);
+/// assert_eq!(
+///     vec![(3, Error::InvalidCodePoint(6, '!')), (3, Error::InvalidLength)],
+///     errors
+/// );
+/// ```
+///
+/// [`decode`]: fn.decode.html
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+/// [`Error::InvalidLength`]: enum.Error.html#variant.InvalidLength
+pub fn decode_lossy<T>(input: &T) -> (Vec<u8>, Vec<(usize, Error)>)
+where
+    T: ?Sized + AsRef<str>,
+{
+    let input = input.as_ref();
+    let mut buf = Vec::with_capacity(input.len());
+    let mut errors = Vec::new();
+
+    let mut done = false;
+    for (index, code_point) in input.char_indices() {
+        match decode_code_point(code_point) {
+            Some((byte1, byte2)) => {
+                if done {
+                    errors.push((buf.len(), Error::InvalidLength));
+                }
+
+                buf.push(byte1);
+                if let Some(byte2) = byte2 {
+                    buf.push(byte2);
+                }
+                done = byte2.is_none();
+            }
+            None => errors.push((buf.len(), Error::InvalidCodePoint(index, code_point))),
+        }
+    }
+
+    (buf, errors)
+}
@@ -0,0 +1,246 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An order-preserving variant of base65536, for keys in sorted stores.
+//!
+//! Comparing base65536 strings doesn't compare the data they encode, as the
+//! first octet of each pair picks a character within a block, while the
+//! second picks the block. This variant uses exactly the same characters,
+//! but assigns them so that comparing encoded strings by code point, or by
+//! their UTF-8 or UTF-32 encodings, gives the same order as comparing the
+//! data octet by octet.
+//!
+//! Every character of base65536 is numbered in order of code point, starting
+//! with the padding block, which comes first. For each possible first octet
+//! there are 257 characters in a row: one for when the data ends after that
+//! octet, followed by one for each possible second octet. So data that ends
+//! sorts before any data continuing from it, just as a prefix does.
+//!
+//! As the characters are the same, this variant can't be told apart from
+//! base65536 by looking at it, and decoding one as the other gives the wrong
+//! data. The API mirrors that of the crate root.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<std::error::Error>> {
+//! use base65536::ordered::{decode, encode};
+//!
+//! let mut keys = vec![&b"b"[..], b"ab", b"a", b"a\x00", b"", b"\xFF"];
+//! let mut encoded: Vec<String> = keys.iter().map(|key| encode(key, None)).collect();
+//! keys.sort();
+//! encoded.sort();
+//!
+//! for (key, encoded) in keys.iter().zip(&encoded) {
+//!     assert_eq!(key, &&decode(encoded, false)?[..]);
+//! }
+//! # Ok(()) }
+//! # test().unwrap();
+//! ```
+
+pub use crate::WrapOptions;
+
+use crate::width::display_width;
+use crate::{decode_code_point, encode_code_point, write_lines, DecodeResult, Error};
+
+// The characters for each first octet: one for ending, and one for each
+// second octet.
+const RUN: usize = 257;
+
+// The position of a character among all base65536 characters in order of
+// code point. This relies on the padding block being below every other
+// block, and on the other blocks being in order.
+#[inline]
+fn position(byte1: u8, byte2: Option<u8>) -> usize {
+    let block = byte2.map_or(0, |byte2| usize::from(byte2) + 1);
+    block * 256 + usize::from(byte1)
+}
+
+#[inline]
+fn encode_position(position: usize) -> char {
+    let (block, byte1) = (position / 256, position as u8);
+    let byte2 = if block == 0 {
+        None
+    } else {
+        Some((block - 1) as u8)
+    };
+    encode_code_point(byte1, byte2)
+}
+
+#[inline]
+fn inner_decode<F>(input: &str, ignore_garbage: bool, mut out: F) -> DecodeResult<()>
+where
+    F: FnMut(u8, Option<u8>),
+{
+    let mut done = false;
+    for (index, code_point) in input.char_indices() {
+        match decode_code_point(code_point) {
+            Some(_) if done => return Err(Error::InvalidLength),
+            Some((byte1, byte2)) => {
+                let position = position(byte1, byte2);
+                let (first, rest) = ((position / RUN) as u8, position % RUN);
+                if rest == 0 {
+                    out(first, None);
+                    done = true;
+                } else {
+                    out(first, Some((rest - 1) as u8));
+                }
+            }
+            None if ignore_garbage => {}
+            None => return Err(Error::InvalidCodePoint(index, code_point)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode from a reference to an order-preserving base65536 string as octets.
+///
+/// # Errors
+///
+/// The same as [`decode`] in the crate root: if the input string contains a
+/// character that isn't base65536, [`Error::InvalidCodePoint`] is returned,
+/// unless using the `ignore_garbage` option, and if the stream continues after
+/// a character ending the data, [`Error::InvalidLength`] is.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::ordered::decode;
+///
+/// assert_eq!(vec![1, 2, 3], decode("㐄㘃", false)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: ../fn.decode.html
+/// [`Error::InvalidCodePoint`]: ../enum.Error.html#variant.InvalidCodePoint
+/// [`Error::InvalidLength`]: ../enum.Error.html#variant.InvalidLength
+pub fn decode<T>(input: &T, ignore_garbage: bool) -> DecodeResult<Vec<u8>>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let mut buf = Vec::with_capacity(input.as_ref().len());
+    decode_buf(input, &mut buf, ignore_garbage).map(|_| buf)
+}
+
+/// Decode from a reference to an order-preserving base65536 string as octets.
+/// Writes into the supplied output buffer, growing it if needed.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// [`decode`]: fn.decode.html
+pub fn decode_buf<T>(input: &T, buf: &mut Vec<u8>, ignore_garbage: bool) -> DecodeResult<()>
+where
+    T: ?Sized + AsRef<str>,
+{
+    inner_decode(input.as_ref(), ignore_garbage, |a, b| {
+        buf.push(a);
+        if let Some(b) = b {
+            buf.push(b)
+        }
+    })
+}
+
+/// Decode from a reference to an order-preserving base65536 string as octets.
+/// Writes into the supplied slice, returning how many bytes were written.
+///
+/// # Panics
+///
+/// Panics if the slice is not long enough.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// [`decode`]: fn.decode.html
+pub fn decode_slice<T>(input: &T, buf: &mut [u8], ignore_garbage: bool) -> DecodeResult<usize>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let mut pos = 0;
+    inner_decode(input.as_ref(), ignore_garbage, |a, b| {
+        buf[pos] = a;
+        pos += 1;
+        if let Some(b) = b {
+            buf[pos] = b;
+            pos += 1;
+        }
+    })
+    .map(|_| pos)
+}
+
+/// Encode arbitrary octets as order-preserving base65536.
+///
+/// The `wrap` option allows wrapping the output every so many characters,
+/// optionally with a supplied string using [`WrapOptions`]. Wrapped output no
+/// longer sorts in the same order as the data.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::ordered::encode;
+///
+/// assert_eq!("㐄㘃", encode(&[1, 2, 3], None));
+/// assert!(encode(&[1, 2], None) < encode(&[1, 2, 0], None));
+/// assert!(encode(&[1, 2, 255], None) < encode(&[1, 3], None));
+/// ```
+///
+/// [`WrapOptions`]: ../enum.WrapOptions.html
+pub fn encode<'a, T, W>(input: &T, wrap: W) -> String
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    // As with base65536, every two bytes of input becomes a character of up
+    // to four bytes. This allocates for the unwrapped output.
+    let mut output = String::with_capacity(input.as_ref().len() * 2);
+    encode_buf(input, &mut output, wrap);
+    output
+}
+
+/// Encode arbitrary octets as order-preserving base65536. Writes into the
+/// supplied output buffer, growing it if needed.
+///
+/// The `wrap` option allows wrapping the output every so many characters,
+/// optionally with a supplied string using [`WrapOptions`].
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// [`WrapOptions`]: ../enum.WrapOptions.html
+pub fn encode_buf<'a, T, W>(input: &T, buf: &mut String, wrap: W)
+where
+    T: ?Sized + AsRef<[u8]>,
+    W: Into<WrapOptions<'a>>,
+{
+    let format = wrap.into().line_format();
+    let chars = input.as_ref().chunks(2).map(|bytes| {
+        let rest = bytes.get(1).map_or(0, |&byte2| usize::from(byte2) + 1);
+        let code_point = encode_position(usize::from(bytes[0]) * RUN + rest);
+        (
+            code_point,
+            format.measure.char_width(code_point, display_width),
+        )
+    });
+    write_lines(chars, buf, format);
+}
@@ -0,0 +1,132 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bit packing shared by qntm's encodings that aren't aligned to octets.
+//!
+//! Octets are packed most significant bit first into characters that each
+//! carry a fixed number of bits. The final character may instead carry fewer
+//! bits, from a smaller repertoire, and any bits left over at the end are
+//! ones.
+
+use std::{marker::PhantomData, slice};
+
+/// A repertoire of characters carrying a fixed number of bits each.
+pub(crate) trait Packing {
+    /// The number of bits carried by every character but the final one.
+    const BITS: u32;
+    /// The number of bits carried by a short final character.
+    const FINAL_BITS: u32;
+
+    /// Encodes `BITS` bits, or `FINAL_BITS` if `is_final` is set, as a single
+    /// character.
+    fn encode(bits: u32, is_final: bool) -> char;
+
+    /// Decodes a single character into the bits it carries, and whether it's
+    /// a short final character. Returns `None` if it isn't in the repertoire.
+    fn decode(code_point: char) -> Option<(u32, bool)>;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Why packed data couldn't be decoded.
+pub(crate) enum Fault {
+    /// A character not in the repertoire, and its offset.
+    CodePoint(usize, char),
+    /// Characters continued after a short final character.
+    Length,
+    /// The bits left over at the end weren't all ones.
+    Padding,
+}
+
+/// An iterator over the characters encoding some octets.
+pub(crate) struct Chars<'a, P> {
+    input: slice::Iter<'a, u8>,
+    bits: u32,
+    len: u32,
+    packing: PhantomData<P>,
+}
+
+impl<'a, P> Chars<'a, P> {
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        Chars {
+            input: input.iter(),
+            bits: 0,
+            len: 0,
+            packing: PhantomData,
+        }
+    }
+}
+
+impl<'a, P: Packing> Iterator for Chars<'a, P> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        while self.len < P::BITS {
+            match self.input.next() {
+                Some(&byte) => {
+                    self.bits = self.bits << 8 | u32::from(byte);
+                    self.len += 8;
+                }
+                None if self.len == 0 => return None,
+                None => {
+                    // pad the final bits with ones, to FINAL_BITS if they'll fit
+                    let is_final = self.len <= P::FINAL_BITS;
+                    let padding = if is_final { P::FINAL_BITS } else { P::BITS } - self.len;
+                    let bits = self.bits << padding | ((1 << padding) - 1);
+                    self.len = 0;
+                    return Some(P::encode(bits, is_final));
+                }
+            }
+        }
+
+        self.len -= P::BITS;
+        let bits = self.bits >> self.len;
+        self.bits &= (1 << self.len) - 1;
+        Some(P::encode(bits, false))
+    }
+}
+
+/// Decodes packed characters, passing each octet to `out`.
+pub(crate) fn decode<P, F>(input: &str, ignore_garbage: bool, mut out: F) -> Result<(), Fault>
+where
+    P: Packing,
+    F: FnMut(u8),
+{
+    let mut bits: u32 = 0;
+    let mut len = 0;
+    let mut done = false;
+    for (index, code_point) in input.char_indices() {
+        match P::decode(code_point) {
+            Some(_) if done => return Err(Fault::Length),
+            Some((value, is_final)) => {
+                let value_len = if is_final { P::FINAL_BITS } else { P::BITS };
+                bits = bits << value_len | value;
+                len += value_len;
+                while len >= 8 {
+                    len -= 8;
+                    out((bits >> len) as u8);
+                }
+                bits &= (1 << len) - 1;
+                done = is_final;
+            }
+            None if ignore_garbage => {}
+            None => return Err(Fault::CodePoint(index, code_point)),
+        }
+    }
+
+    if bits == (1 << len) - 1 {
+        Ok(())
+    } else {
+        Err(Fault::Padding)
+    }
+}
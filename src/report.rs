@@ -0,0 +1,160 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{inner_decode_with_garbage, DecodeResult, BLOCK_START_TO_INDEX, PADDING_BLOCK_START};
+use std::ops::Range;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A run of consecutive characters skipped while decoding.
+///
+/// Used with [`DecodeReport`].
+///
+/// [`DecodeReport`]: struct.DecodeReport.html
+pub struct SkippedSpan {
+    /// The range of the run in the input, in bytes.
+    pub bytes: Range<usize>,
+    /// The range of the run in the input, in characters.
+    pub chars: Range<usize>,
+}
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+/// A record of every character ignored while decoding.
+///
+/// Returned by [`decode_with_report`]. See it for examples.
+///
+/// [`decode_with_report`]: fn.decode_with_report.html
+pub struct DecodeReport {
+    /// Every run of skipped characters, in the order they were found.
+    pub spans: Vec<SkippedSpan>,
+    /// How many skipped characters were whitespace.
+    pub whitespace: usize,
+    /// How many skipped characters were ASCII, but not whitespace.
+    pub ascii: usize,
+    /// How many skipped characters were neither ASCII nor whitespace.
+    pub other: usize,
+    /// Whether any skipped character was in a block directly adjacent to a
+    /// base65536 block, which suggests a mangled base65536 character rather
+    /// than ordinary garbage.
+    pub near_miss: bool,
+}
+
+impl DecodeReport {
+    /// Returns `true` if no characters were skipped.
+    pub fn is_clean(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The total number of characters skipped.
+    pub fn skipped(&self) -> usize {
+        self.whitespace + self.ascii + self.other
+    }
+}
+
+fn is_base65536_block(block_start: u32) -> bool {
+    block_start == PADDING_BLOCK_START || BLOCK_START_TO_INDEX.contains_key(&block_start)
+}
+
+fn is_near_miss(code_point: char) -> bool {
+    const BLOCK_SIZE: u32 = 1 << 8;
+    let block_start = code_point as u32 & !(BLOCK_SIZE - 1);
+
+    (block_start >= BLOCK_SIZE && is_base65536_block(block_start - BLOCK_SIZE))
+        || is_base65536_block(block_start + BLOCK_SIZE)
+}
+
+/// Decode from a reference to a base65536-encoded string as octets, recording
+/// every character that was skipped along the way.
+///
+/// This behaves exactly like [`decode`], but also returns a [`DecodeReport`]
+/// describing what was ignored, so that garbage dropped with the
+/// `ignore_garbage` option can be audited. Without `ignore_garbage` nothing
+/// is ever skipped, and the report is always empty.
+///
+/// # Errors
+///
+/// The same as [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::decode_with_report;
+///
+/// let (buf, report) = decode_with_report("㘁 asdf\nᔃ", true)?;
+/// assert_eq!(vec![1, 2, 3], buf);
+/// assert_eq!(1, report.spans.len());
+/// assert_eq!(1..7, report.spans[0].chars);
+/// assert_eq!(2, report.whitespace);
+/// assert_eq!(4, report.ascii);
+/// assert!(!report.near_miss);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: fn.decode.html
+/// [`DecodeReport`]: struct.DecodeReport.html
+pub fn decode_with_report<T>(
+    input: &T,
+    ignore_garbage: bool,
+) -> DecodeResult<(Vec<u8>, DecodeReport)>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let input = input.as_ref();
+    let mut buf = Vec::with_capacity(input.len());
+    let mut report = DecodeReport::default();
+
+    // character offsets are counted incrementally, from the end of the last
+    // skipped character, so that clean input isn't counted at all
+    let mut counted_bytes = 0;
+    let mut counted_chars = 0;
+
+    inner_decode_with_garbage(
+        input,
+        ignore_garbage,
+        |a, b| {
+            buf.push(a);
+            if let Some(b) = b {
+                buf.push(b)
+            }
+        },
+        |index, code_point| {
+            let char_index = counted_chars + input[counted_bytes..index].chars().count();
+            let next_index = index + code_point.len_utf8();
+            counted_bytes = next_index;
+            counted_chars = char_index + 1;
+
+            if code_point.is_whitespace() {
+                report.whitespace += 1;
+            } else if code_point.is_ascii() {
+                report.ascii += 1;
+            } else {
+                report.other += 1;
+            }
+            report.near_miss |= is_near_miss(code_point);
+
+            match report.spans.last_mut() {
+                Some(span) if span.bytes.end == index => {
+                    span.bytes.end = next_index;
+                    span.chars.end = char_index + 1;
+                }
+                _ => report.spans.push(SkippedSpan {
+                    bytes: index..next_index,
+                    chars: char_index..char_index + 1,
+                }),
+            }
+        },
+    )
+    .map(|_| (buf, report))
+}
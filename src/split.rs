@@ -0,0 +1,346 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::budget::prefix_len_within;
+use crate::{decode, encode, Budget, Error};
+use std::{error, fmt};
+
+// Every part starts with a header of a 32-bit message id, the 16-bit index of
+// the part, the 16-bit number of parts, and the 32-bit length of the whole
+// message, all big-endian. This is an even number of octets, so it never ends
+// in a padding character.
+const HEADER_LEN: usize = 12;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Header {
+    id: u32,
+    index: u16,
+    total: u16,
+    length: u32,
+}
+
+impl Header {
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        bytes[0..4].copy_from_slice(&self.id.to_be_bytes());
+        bytes[4..6].copy_from_slice(&self.index.to_be_bytes());
+        bytes[6..8].copy_from_slice(&self.total.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.length.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Header> {
+        if bytes.len() < HEADER_LEN {
+            return None;
+        }
+
+        let header = Header {
+            id: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            index: u16::from_be_bytes([bytes[4], bytes[5]]),
+            total: u16::from_be_bytes([bytes[6], bytes[7]]),
+            length: u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+        };
+
+        if header.index < header.total {
+            Some(header)
+        } else {
+            None
+        }
+    }
+}
+
+// The message id is the 32-bit FNV-1a hash of the message, which lets the
+// reassembled message be checked as well.
+fn message_id(input: &[u8]) -> u32 {
+    input.iter().fold(0x811C_9DC5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// Represents an error while splitting a message, or reassembling it.
+///
+/// Used with [`split_encode`] and [`Reassembler`]. See them for examples.
+///
+/// [`split_encode`]: fn.split_encode.html
+/// [`Reassembler`]: struct.Reassembler.html
+pub enum SplitError {
+    /// The part budget isn't enough for the header and at least one octet of
+    /// the message.
+    BudgetTooSmall,
+    /// The message would take more than 65535 parts, or is longer than 4 GiB.
+    TooLong,
+    /// A part wasn't valid base65536.
+    Decode(Error),
+    /// A part didn't start with a valid header.
+    InvalidHeader,
+    /// A part belongs to a different message than the parts before it.
+    ///
+    /// Contains the id of the message it belongs to.
+    WrongMessage(u32),
+    /// A part was added more than once.
+    ///
+    /// Contains the index of the part.
+    Duplicate(u16),
+    /// Not every part has been added.
+    ///
+    /// Contains the indices of the missing parts, which is empty if no parts
+    /// were added at all, as the number of parts isn't known then.
+    Missing(Vec<u16>),
+    /// Every part was added, but the reassembled message doesn't match the
+    /// length or id given in the headers.
+    Corrupt,
+}
+
+impl fmt::Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SplitError::BudgetTooSmall => write!(f, "part budget too small for any data"),
+            SplitError::TooLong => write!(f, "message too long to split"),
+            SplitError::Decode(ref err) => write!(f, "{}", err),
+            SplitError::InvalidHeader => write!(f, "invalid part header"),
+            SplitError::WrongMessage(id) => write!(f, "part of another message ({:08x})", id),
+            SplitError::Duplicate(index) => write!(f, "duplicate part {}", index),
+            SplitError::Missing(ref missing) => write!(f, "{} missing parts", missing.len()),
+            SplitError::Corrupt => write!(f, "reassembled message is corrupt"),
+        }
+    }
+}
+
+impl error::Error for SplitError {
+    fn description(&self) -> &str {
+        match *self {
+            SplitError::BudgetTooSmall => "part budget too small",
+            SplitError::TooLong => "message too long",
+            SplitError::Decode(_) => "invalid base65536",
+            SplitError::InvalidHeader => "invalid part header",
+            SplitError::WrongMessage(_) => "wrong message",
+            SplitError::Duplicate(_) => "duplicate part",
+            SplitError::Missing(_) => "missing parts",
+            SplitError::Corrupt => "corrupt message",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            SplitError::Decode(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Encode arbitrary octets as several parts of base65536, each within a
+/// limited length.
+///
+/// Every part starts with a header, which identifies the message and the
+/// part's place in it, so that a [`Reassembler`] can put the parts back
+/// together in any order. The header takes up six characters of each part.
+/// Each part is otherwise filled with as much of the input as fits.
+///
+/// # Errors
+///
+/// Returns [`SplitError::BudgetTooSmall`] if `part_budget` isn't enough for
+/// the header and at least one octet of input, and [`SplitError::TooLong`] if
+/// the input is so long that it would take more than 65535 parts or is longer
+/// than 4 GiB.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::{split_encode, Budget, Reassembler};
+///
+/// let input = vec![42; 1000];
+/// let parts = split_encode(&input, Budget::CodePoints(140))?;
+/// assert_eq!(4, parts.len());
+/// assert!(parts.iter().all(|part| part.chars().count() <= 140));
+///
+/// let mut reassembler = Reassembler::new();
+/// for part in parts.iter().rev() {
+///     reassembler.add(part)?;
+/// }
+/// assert_eq!(input, reassembler.finish()?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Reassembler`]: struct.Reassembler.html
+/// [`SplitError::BudgetTooSmall`]: enum.SplitError.html#variant.BudgetTooSmall
+/// [`SplitError::TooLong`]: enum.SplitError.html#variant.TooLong
+pub fn split_encode<T>(input: &T, part_budget: Budget) -> Result<Vec<String>, SplitError>
+where
+    T: ?Sized + AsRef<[u8]>,
+{
+    let input = input.as_ref();
+    if input.len() > u32::MAX as usize {
+        return Err(SplitError::TooLong);
+    }
+    let budget = part_budget
+        .reserve(HEADER_LEN / 2)
+        .ok_or(SplitError::BudgetTooSmall)?;
+
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    loop {
+        let len = prefix_len_within(rest, budget);
+        if len == 0 && !rest.is_empty() {
+            return Err(SplitError::BudgetTooSmall);
+        }
+
+        chunks.push(&rest[..len]);
+        rest = &rest[len..];
+        if rest.is_empty() {
+            break;
+        }
+        if chunks.len() == u16::MAX as usize {
+            return Err(SplitError::TooLong);
+        }
+    }
+
+    let id = message_id(input);
+    let total = chunks.len() as u16;
+    Ok(chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let header = Header {
+                id,
+                index: index as u16,
+                total,
+                length: input.len() as u32,
+            };
+
+            let mut part = Vec::with_capacity(HEADER_LEN + chunk.len());
+            part.extend_from_slice(&header.to_bytes());
+            part.extend_from_slice(chunk);
+            encode(&part, None)
+        })
+        .collect())
+}
+
+#[derive(Clone, Debug, Default)]
+/// Puts together a message split by [`split_encode`].
+///
+/// Parts may be added in any order. Once every part has been added, the
+/// message can be taken out with [`finish`].
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{split_encode, Budget, Reassembler, SplitError};
+///
+/// let parts = split_encode(&[42; 1000][..], Budget::CodePoints(140)).unwrap();
+///
+/// let mut reassembler = Reassembler::new();
+/// reassembler.add(&parts[3]).unwrap();
+/// reassembler.add(&parts[0]).unwrap();
+/// assert_eq!(Err(SplitError::Duplicate(0)), reassembler.add(&parts[0]));
+/// assert_eq!(vec![1, 2], reassembler.missing());
+/// assert!(!reassembler.is_complete());
+/// ```
+///
+/// [`split_encode`]: fn.split_encode.html
+/// [`finish`]: #method.finish
+pub struct Reassembler {
+    first: Option<Header>,
+    parts: Vec<Option<Vec<u8>>>,
+}
+
+impl Reassembler {
+    /// Creates a new, empty `Reassembler`.
+    pub fn new() -> Self {
+        Reassembler::default()
+    }
+
+    /// Adds a single part.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the part isn't valid, belongs to a different
+    /// message than the parts added before it, or was already added. The
+    /// part is ignored if so, and any other parts are unaffected.
+    pub fn add<T>(&mut self, part: &T) -> Result<(), SplitError>
+    where
+        T: ?Sized + AsRef<str>,
+    {
+        let mut part = decode(part, false).map_err(SplitError::Decode)?;
+        let header = Header::from_bytes(&part).ok_or(SplitError::InvalidHeader)?;
+
+        match self.first {
+            None => {
+                self.first = Some(header);
+                self.parts = vec![None; usize::from(header.total)];
+            }
+            Some(first) if first.id != header.id => {
+                return Err(SplitError::WrongMessage(header.id));
+            }
+            Some(first) if first.total != header.total || first.length != header.length => {
+                return Err(SplitError::InvalidHeader);
+            }
+            Some(_) => {}
+        }
+
+        let slot = &mut self.parts[usize::from(header.index)];
+        if slot.is_some() {
+            return Err(SplitError::Duplicate(header.index));
+        }
+
+        part.drain(..HEADER_LEN);
+        *slot = Some(part);
+        Ok(())
+    }
+
+    /// Returns the indices of the parts that haven't been added yet.
+    ///
+    /// This is empty if no parts were added at all, as the number of parts
+    /// isn't known then.
+    pub fn missing(&self) -> Vec<u16> {
+        (0..self.parts.len())
+            .filter(|&index| self.parts[index].is_none())
+            .map(|index| index as u16)
+            .collect()
+    }
+
+    /// Returns `true` if every part has been added.
+    pub fn is_complete(&self) -> bool {
+        self.first.is_some() && self.parts.iter().all(Option::is_some)
+    }
+
+    /// Puts the parts together, returning the original message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SplitError::Missing`] if not every part has been added, and
+    /// [`SplitError::Corrupt`] if the message doesn't match its headers.
+    ///
+    /// [`SplitError::Missing`]: enum.SplitError.html#variant.Missing
+    /// [`SplitError::Corrupt`]: enum.SplitError.html#variant.Corrupt
+    pub fn finish(self) -> Result<Vec<u8>, SplitError> {
+        if !self.is_complete() {
+            return Err(SplitError::Missing(self.missing()));
+        }
+
+        let first = self.first.unwrap();
+        let mut message = Vec::with_capacity(first.length as usize);
+        for part in self.parts {
+            message.extend_from_slice(&part.unwrap());
+        }
+
+        if message.len() == first.length as usize && message_id(&message) == first.id {
+            Ok(message)
+        } else {
+            Err(SplitError::Corrupt)
+        }
+    }
+}
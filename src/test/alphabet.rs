@@ -0,0 +1,152 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::sample;
+use crate::alphabet::{Alphabet, AlphabetError, Engine};
+use crate::{decode, encode, Error, WrapOptions, BLOCK_STARTS, PADDING_BLOCK_START};
+
+// the blocks from U+10000, in order, with the padding block at U+1500
+fn plane_one() -> Alphabet {
+    let block_starts: Vec<u32> = (0x100..0x200).map(|block| block << 8).collect();
+    Alphabet::new(&block_starts, 0x1500).unwrap()
+}
+
+#[test]
+fn standard() {
+    let engine = Engine::default();
+    assert_eq!(engine.alphabet().block_starts(), BLOCK_STARTS);
+    assert_eq!(engine.alphabet().padding_block_start(), PADDING_BLOCK_START);
+
+    let wraps = [
+        WrapOptions::NoWrap,
+        WrapOptions::WrapAt(7),
+        WrapOptions::WrapAtDisplayWidth(7),
+        WrapOptions::WrapAtUtf8Len(7),
+        WrapOptions::WrapAtUtf16Len(7),
+    ];
+    for len in 0..600usize {
+        let input = sample(len);
+        for &wrap in &wraps {
+            assert_eq!(engine.encode(&input, wrap), encode(&input, wrap));
+        }
+
+        let encoded = encode(&input, None);
+        assert_eq!(engine.decode(&encoded, false), decode(&encoded, false));
+    }
+}
+
+#[test]
+fn custom() {
+    let engine = Engine::new(plane_one());
+    let input: Vec<u8> = (0..=255).flat_map(|byte| vec![byte, 0xFF - byte]).collect();
+    let encoded = engine.encode(&input, None);
+    assert!(encoded
+        .chars()
+        .all(|c| (0x10000..0x20000).contains(&(c as u32))));
+    assert_eq!(engine.decode(&encoded, false).unwrap(), input);
+
+    assert_eq!(
+        engine.encode("hello world", None),
+        "\u{16568}\u{16C6C}\u{1206F}\u{16F77}\u{16C72}ᕤ"
+    );
+    assert_eq!(
+        engine
+            .decode("\u{16568}\u{16C6C}\u{1206F}\u{16F77}\u{16C72}ᕤ", false)
+            .unwrap(),
+        b"hello world"
+    );
+
+    let mut buf = Vec::new();
+    engine.decode_buf(&encoded, &mut buf, false).unwrap();
+    assert_eq!(buf, input);
+
+    let mut slice = [0; 512];
+    assert_eq!(
+        engine.decode_slice(&encoded, &mut slice, false).unwrap(),
+        512
+    );
+    assert_eq!(&slice[..], &input[..]);
+}
+
+#[test]
+fn custom_invalid() {
+    let engine = Engine::new(plane_one());
+    // standard base65536 isn't valid in another alphabet
+    assert_eq!(
+        engine.decode("驨ꍬ", false),
+        Err(Error::InvalidCodePoint(0, '驨'))
+    );
+    assert_eq!(engine.decode("驨\u{16568}ᕤ", true).unwrap(), b"he\x64");
+    assert_eq!(
+        engine.decode("ᔁ\u{10000}", false),
+        Err(Error::InvalidLength)
+    );
+}
+
+#[test]
+fn custom_wrap() {
+    let engine = Engine::new(plane_one());
+    let input = [0; 8];
+
+    let mut buf = String::new();
+    engine.encode_buf(&input[..], &mut buf, 3);
+    assert_eq!(buf, "\u{10000}\u{10000}\u{10000}\n\u{10000}");
+    // every character is four bytes of UTF-8 and two code units of UTF-16
+    assert_eq!(
+        engine.encode(&input[..], WrapOptions::WrapAtUtf8Len(9)),
+        "\u{10000}\u{10000}\n\u{10000}\u{10000}"
+    );
+    assert_eq!(
+        engine.encode(&input[..], WrapOptions::WrapAtUtf16Len(5)),
+        "\u{10000}\u{10000}\n\u{10000}\u{10000}"
+    );
+    assert_eq!(engine.decode(&buf, true).unwrap(), input);
+}
+
+#[test]
+fn invalid_alphabets() {
+    let block_starts: Vec<u32> = (0x100..0x200).map(|block| block << 8).collect();
+    assert_eq!(
+        Alphabet::new(&[], 0x1500),
+        Err(AlphabetError::WrongBlockCount(0))
+    );
+    assert_eq!(
+        Alphabet::new(&[block_starts.clone(), vec![0x3400]].concat(), 0x1500),
+        Err(AlphabetError::WrongBlockCount(257))
+    );
+
+    let mut misaligned = block_starts.clone();
+    misaligned[3] += 1;
+    assert_eq!(
+        Alphabet::new(&misaligned, 0x1500),
+        Err(AlphabetError::Misaligned(0x10301))
+    );
+
+    let mut duplicate = block_starts.clone();
+    duplicate[255] = duplicate[0];
+    assert_eq!(
+        Alphabet::new(&duplicate, 0x1500),
+        Err(AlphabetError::Duplicate(0x10000))
+    );
+
+    for &start in &[0xD800, 0xDF00, 0x110000, 0xFFFF_FF00] {
+        assert_eq!(
+            Alphabet::new(&block_starts, start),
+            Err(AlphabetError::InvalidBlock(start))
+        );
+    }
+    assert!(Alphabet::new(&block_starts, 0xD700).is_ok());
+    assert!(Alphabet::new(&block_starts, 0xE000).is_ok());
+    assert!(Alphabet::new(&block_starts, 0x10FF00).is_ok());
+}
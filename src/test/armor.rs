@@ -0,0 +1,143 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Error, WrapOptions};
+use crate::armor::{armor, crc32, dearmor, ArmorError};
+
+#[test]
+fn crc() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32(b"hello world"), 0x0D4A_1185);
+}
+
+#[test]
+fn format() {
+    assert_eq!(
+        armor("hello world", "GREETING", &[("Comment", "an example")], 3).unwrap(),
+        "----- BEGIN BASE65536 GREETING -----\n\
+         Comment: an example\n\
+         \n\
+         驨ꍬ啯\n\
+         𒁷ꍲᕤ\n\
+         =0D4A1185\n\
+         ----- END BASE65536 GREETING -----\n"
+    );
+    assert_eq!(
+        armor("", "EMPTY", &[], None).unwrap(),
+        "----- BEGIN BASE65536 EMPTY -----\n\
+         =00000000\n\
+         ----- END BASE65536 EMPTY -----\n"
+    );
+}
+
+#[test]
+fn round_trip() {
+    let input: Vec<u8> = (0..1001).map(|i| (i * 31) as u8).collect();
+    let headers: &[&[(&str, &str)]] = &[
+        &[],
+        &[("A", "b"), ("Empty", "")],
+        &[("Two Words", "a: b  c"), ("Key", "value : with colons:")],
+    ];
+    for headers in headers {
+        for wrap in &[
+            WrapOptions::NoWrap,
+            WrapOptions::WrapAt(64),
+            WrapOptions::WrapAtWith(64, "\r\n"),
+            WrapOptions::WrapAt(64).with_trailing_eol().unwrap(),
+        ] {
+            let armored = armor(&input, "DATA FILE", headers, *wrap).unwrap();
+            let block = dearmor(&armored).unwrap();
+            assert_eq!(block.label, "DATA FILE");
+            assert_eq!(block.data, input);
+
+            let expected: Vec<_> = headers
+                .iter()
+                .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+                .collect();
+            assert_eq!(block.headers, expected);
+        }
+    }
+}
+
+#[test]
+fn surrounding_text() {
+    let armored = armor("hello world", "GREETING", &[], 3).unwrap();
+    let text = format!(
+        "> ----- BEGIN BASE65536 QUOTED -----\n\
+         ----- BEGIN BASE65536 -----\n\
+         hi,\r\n\r\n{}\r\nbye  \n",
+        armored.replace('\n', "  \r\n")
+    );
+    assert_eq!(dearmor(&text).unwrap().data, b"hello world");
+}
+
+#[test]
+fn invalid_input() {
+    assert_eq!(armor("", "", &[], None), Err(ArmorError::InvalidLabel));
+    assert_eq!(
+        armor("", "A-----B", &[], None),
+        Err(ArmorError::InvalidLabel)
+    );
+    assert_eq!(armor("", "A\nB", &[], None), Err(ArmorError::InvalidLabel));
+    assert_eq!(
+        armor("", "A", &[("Key:", "value")], None),
+        Err(ArmorError::InvalidHeader)
+    );
+    assert_eq!(
+        armor("", "A", &[("Key", "two\nlines")], None),
+        Err(ArmorError::InvalidHeader)
+    );
+    for &(key, value) in &[("Key ", "value"), ("Key", " value"), ("Key", "value ")] {
+        assert_eq!(
+            armor("", "A", &[(key, value)], None),
+            Err(ArmorError::InvalidHeader)
+        );
+    }
+}
+
+#[test]
+fn broken_blocks() {
+    let armored = armor("hello world", "GREETING", &[("Key", "value")], 3).unwrap();
+    let lines: Vec<&str> = armored.lines().collect();
+    let without = |skip: usize| {
+        let mut lines = lines.clone();
+        lines.remove(skip);
+        lines.join("\n")
+    };
+
+    assert_eq!(dearmor("nothing here"), Err(ArmorError::MissingBegin));
+    assert_eq!(dearmor(&without(0)), Err(ArmorError::MissingBegin));
+    assert_eq!(dearmor(&without(5)), Err(ArmorError::MissingChecksum));
+    assert_eq!(dearmor(&without(6)), Err(ArmorError::MissingEnd));
+    assert_eq!(
+        dearmor(&armored.replace("Key: value\n", "Key: value\nno colon\n")),
+        Err(ArmorError::InvalidHeader)
+    );
+    assert_eq!(
+        dearmor(&armored.replace("END BASE65536 GREETING", "END BASE65536 OTHER")),
+        Err(ArmorError::MissingEnd)
+    );
+    assert_eq!(
+        dearmor(&armored.replace("驨ꍬ啯", "驨ꍬ啯!")),
+        Err(ArmorError::Decode(Error::InvalidCodePoint(9, '!')))
+    );
+    assert_eq!(
+        dearmor(&armored.replace("驨ꍬ啯", "驨ꍬ啰")),
+        Err(ArmorError::ChecksumMismatch(
+            0x0D4A_1185,
+            crc32(b"hellp world")
+        ))
+    );
+}
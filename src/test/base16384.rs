@@ -0,0 +1,131 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::check_round_trip;
+use crate::base16384::{decode, decode_buf, decode_slice, encode, encode_buf, Error, WrapOptions};
+
+const VECTORS: &[(&[u8], &str)] = &[
+    (b"", ""),
+    (b"\x00", "一㴁"),
+    (b"\xFF", "跀㴁"),
+    (b"\x01\x02\x03", "乀渰㴃"),
+    (&[0xFF; 7], "跿跿跿跿"),
+    (b"\x00\x01\x02\x03\x04\x05\x06", "一帠娐匆"),
+    (b"abcdef", "晘琶徕琀㴆"),
+    (b"abcdefg", "晘琶徕瑧"),
+    (b"hello world", "栙擆羼湷槜瓆帀㴄"),
+];
+
+#[test]
+fn vectors() {
+    for &(bytes, text) in VECTORS {
+        assert_eq!(encode(bytes, None), text);
+        assert_eq!(decode(text, false).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn repertoire() {
+    // every 14-bit value at the start of a group
+    for value in 0..1u32 << 14 {
+        let input = [(value >> 6) as u8, (value << 2) as u8];
+        let encoded: Vec<char> = encode(&input, None).chars().collect();
+        assert_eq!(encoded[0] as u32, 0x4E00 + value);
+        assert_eq!(encoded.last(), Some(&'\u{3D02}'));
+        assert_eq!(decode(&encode(&input, None), false).unwrap(), input);
+    }
+}
+
+#[test]
+fn round_trip() {
+    let encoded = check_round_trip(
+        |input| encode(input, None),
+        |text| decode(text, false),
+        |text, buf| decode_buf(text, buf, false),
+        |text, buf| decode_slice(text, buf, false),
+    );
+    for (len, encoded) in encoded.iter().enumerate() {
+        let tail = if len % 7 == 0 { 0 } else { 1 };
+        let chars = len / 7 * 4 + (len % 7 * 8).div_ceil(14) + tail;
+        assert_eq!(encoded.chars().count(), chars);
+    }
+}
+
+#[test]
+fn byte_order_mark() {
+    assert_eq!(
+        decode("\u{FEFF}栙擆羼湷槜瓆帀㴄", false).unwrap(),
+        b"hello world"
+    );
+    assert_eq!(
+        decode("\u{FEFF}栙!", false),
+        Err(Error::InvalidCodePoint("\u{FEFF}栙".len(), '!'))
+    );
+    // only at the beginning
+    assert_eq!(
+        decode("栙\u{FEFF}", false),
+        Err(Error::InvalidCodePoint("栙".len(), '\u{FEFF}'))
+    );
+}
+
+#[test]
+fn wrap() {
+    assert_eq!(encode("hello world", 4), "栙擆羼湷\n槜瓆帀㴄");
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtWith(3, "\r\n")),
+        "栙擆羼\r\n湷槜瓆\r\n帀㴄"
+    );
+    // every character takes up two terminal cells
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtDisplayWidth(5)),
+        "栙擆\n羼湷\n槜瓆\n帀㴄"
+    );
+
+    let mut buf = String::new();
+    encode_buf("hello world", &mut buf, 4);
+    assert_eq!(decode(&buf, true).unwrap(), b"hello world");
+    assert_eq!(
+        decode(&buf, false),
+        Err(Error::InvalidCodePoint("栙擆羼湷".len(), '\n'))
+    );
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(decode("栙!", false), Err(Error::InvalidCodePoint(3, '!')));
+    assert_eq!(decode("!栙擆羼湷槜瓆帀㴄", true).unwrap(), b"hello world");
+    // past either end of the repertoire, or a tail marker out of range
+    assert_eq!(
+        decode("\u{4DFF}", false),
+        Err(Error::InvalidCodePoint(0, '\u{4DFF}'))
+    );
+    assert_eq!(
+        decode("\u{8E00}", false),
+        Err(Error::InvalidCodePoint(0, '\u{8E00}'))
+    );
+    assert_eq!(
+        decode("一\u{3D07}", false),
+        Err(Error::InvalidCodePoint(3, '\u{3D07}'))
+    );
+    // a short final group without a tail marker
+    assert_eq!(decode("晘琶徕", false), Err(Error::InvalidLength));
+    // a tail marker that doesn't match the group before it
+    assert_eq!(decode("晘琶徕琀㴅", false), Err(Error::InvalidLength));
+    assert_eq!(decode("晘琶徕瑧㴁", false), Err(Error::InvalidLength));
+    assert_eq!(decode("㴁", false), Err(Error::InvalidLength));
+    // anything after a tail marker
+    assert_eq!(decode("一㴁一㴁", false), Err(Error::InvalidLength));
+    assert_eq!(decode("一㴁 一", true), Err(Error::InvalidLength));
+    assert_eq!(decode("一㴁\n", true).unwrap(), b"\x00");
+}
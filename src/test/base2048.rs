@@ -0,0 +1,138 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::check_round_trip;
+use crate::base2048::{decode, decode_buf, decode_slice, encode, encode_buf, Error, WrapOptions};
+use crate::twitter;
+use std::collections::HashSet;
+
+const VECTORS: &[(&[u8], &str)] = &[
+    (b"", ""),
+    (b"\x00", "F"),
+    (b"\xFF", "ၕ"),
+    (b"\x01\x02\x03", "GƷ7"),
+    (&[0x00; 11], "88888888"),
+    (&[0xFF; 11], "ၕၕၕၕၕၕၕၕ"),
+    (b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A", "8đߝđՌaʛʤ"),
+    (b"hello world", "ڵϠɲණæஊಢࢷ"),
+];
+
+// every 11-bit value, in order, packed most significant bit first
+fn every_value() -> Vec<u8> {
+    let mut input = Vec::new();
+    let (mut bits, mut len) = (0u32, 0);
+    for value in 0..1 << 11 {
+        bits = bits << 11 | value;
+        len += 11;
+        while len >= 8 {
+            len -= 8;
+            input.push((bits >> len) as u8);
+        }
+        bits &= (1 << len) - 1;
+    }
+    assert_eq!(len, 0);
+    input
+}
+
+#[test]
+fn vectors() {
+    for &(bytes, text) in VECTORS {
+        assert_eq!(encode(bytes, None), text);
+        assert_eq!(decode(text, false).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn repertoire() {
+    let encoded = encode(&every_value(), None);
+    let repertoire: Vec<char> = encoded.chars().collect();
+    assert_eq!(repertoire.len(), 1 << 11);
+
+    // distinct, in order, and all counted once by twitter
+    assert!(repertoire.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(repertoire.iter().all(|&c| c.is_alphanumeric()));
+    assert!(repertoire.iter().all(|c| !('0'..='7').contains(c)));
+    assert_eq!(twitter::weighted_len(&encoded), repertoire.len());
+
+    assert_eq!(decode(&encoded, false).unwrap(), every_value());
+}
+
+#[test]
+fn final_repertoire() {
+    // after seven 11-bit characters, a final character makes up the last
+    // octet exactly, with no padding
+    let mut seen = HashSet::new();
+    for value in 0..8 {
+        let text = format!("8888888{}", value);
+        let mut expected = vec![0; 10];
+        expected[9] = value;
+        assert_eq!(decode(&text, false).unwrap(), expected);
+        assert_eq!(encode(&expected, None), text);
+        seen.insert(text);
+    }
+    assert_eq!(seen.len(), 8);
+}
+
+#[test]
+fn round_trip() {
+    let encoded = check_round_trip(
+        |input| encode(input, None),
+        |text| decode(text, false),
+        |text, buf| decode_buf(text, buf, false),
+        |text, buf| decode_slice(text, buf, false),
+    );
+    for (len, encoded) in encoded.iter().enumerate() {
+        assert_eq!(encoded.chars().count(), (len * 8).div_ceil(11));
+        assert_eq!(twitter::weighted_len(encoded), encoded.chars().count());
+    }
+}
+
+#[test]
+fn wrap() {
+    assert_eq!(encode("hello world", 4), "ڵϠɲණ\næஊಢࢷ");
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtWith(3, "\r\n")),
+        "ڵϠɲ\r\nණæஊ\r\nಢࢷ"
+    );
+    // every character takes up a single terminal cell
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtDisplayWidth(4)),
+        "ڵϠɲණ\næஊಢࢷ"
+    );
+    // but some are two bytes of UTF-8, and others three
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtUtf8Len(6)),
+        "ڵϠɲ\nණæ\nஊಢ\nࢷ"
+    );
+
+    let mut buf = String::new();
+    encode_buf("hello world", &mut buf, 4);
+    assert_eq!(decode(&buf, true).unwrap(), b"hello world");
+    assert_eq!(
+        decode(&buf, false),
+        Err(Error::InvalidCodePoint("ڵϠɲණ".len(), '\n'))
+    );
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(decode("ڵ!", false), Err(Error::InvalidCodePoint(2, '!')));
+    assert_eq!(decode("!ڵϠɲණæஊಢࢷ", true).unwrap(), b"hello world");
+    // a final character before the end
+    assert_eq!(decode("08", false), Err(Error::InvalidLength));
+    assert_eq!(decode("0 8", true), Err(Error::InvalidLength));
+    // leftover bits that aren't ones
+    assert_eq!(decode("8", false), Err(Error::InvalidPadding));
+    assert_eq!(decode("0", false), Err(Error::InvalidPadding));
+}
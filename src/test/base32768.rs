@@ -0,0 +1,148 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::check_round_trip;
+use crate::base32768::{decode, decode_buf, decode_slice, encode, encode_buf, Error, WrapOptions};
+
+// The repertoires as given by the reference implementation, as the first and
+// last character of each range.
+const REPERTOIRE: &str = "ҠҿԀԟڀڿݠޟ߀ߟကဟႠႿᄀᅟᆀᆟᇠሿበቿዠዿጠጿᎠᏟᐠᙟᚠᛟកសᠠᡟᣀᣟᦀᦟ᧠᧿ᨠᨿᯀᯟᰀᰟᴀᴟ⇠⇿⋀⋟⍀⏟␀␟─❟➀➿⠀⥿⦠⦿⨠⩟⪀⪿⫠⭟ⰀⰟⲀⳟⴀⴟⵀⵟ⺠⻟㇀㇟㐀䶟䷀龿ꀀꑿ꒠꒿ꔀꗿꙀꙟꚠꛟ꜀ꝟꞀꞟꡀꡟ";
+const FINAL_REPERTOIRE: &str = "ƀƟɀʟ";
+
+const VECTORS: &[(&[u8], &str)] = &[
+    (b"", ""),
+    (b"\x00", "ڿ"),
+    (b"\xFF", "ꡟ"),
+    (b"\x00\x00", "Ҡɟ"),
+    (b"\xFF\xFF", "ꡟʟ"),
+    (
+        b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E",
+        "Ҡ曠蛠盀庠䩨㱘Ⳏ",
+    ),
+    (&[0xFF; 15], "ꡟꡟꡟꡟꡟꡟꡟꡟ"),
+    (&[0x00; 15], "ҠҠҠҠҠҠҠҠ"),
+    (b"hello world", "媒腻㐤┖ꈳ埳"),
+    (
+        b"Base32768 is a binary-to-text encoding",
+        "䝰茹䲦䦳堡ᇥ負螀垔臸咯㤗䦹就煐骀夗㼛鋭㴦ɟ",
+    ),
+];
+
+fn expand(ranges: &str) -> Vec<char> {
+    let ranges: Vec<char> = ranges.chars().collect();
+    ranges
+        .chunks(2)
+        .flat_map(|range| range[0]..=range[1])
+        .collect()
+}
+
+#[test]
+fn vectors() {
+    for &(bytes, text) in VECTORS {
+        assert_eq!(encode(bytes, None), text);
+        assert_eq!(decode(text, false).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn repertoire() {
+    let repertoire = expand(REPERTOIRE);
+    assert_eq!(repertoire.len(), 1 << 15);
+    assert!(repertoire.iter().all(|c| c.len_utf16() == 1));
+
+    // every 15-bit value, in order, packed most significant bit first
+    let mut input = Vec::new();
+    let (mut bits, mut len) = (0u32, 0);
+    for value in 0..1 << 15 {
+        bits = bits << 15 | value;
+        len += 15;
+        while len >= 8 {
+            len -= 8;
+            input.push((bits >> len) as u8);
+        }
+        bits &= (1 << len) - 1;
+    }
+    assert_eq!(len, 0);
+
+    let encoded = encode(&input, None);
+    assert_eq!(encoded.chars().collect::<Vec<_>>(), repertoire);
+    assert_eq!(decode(&encoded, false).unwrap(), input);
+}
+
+#[test]
+fn final_repertoire() {
+    let repertoire = expand(FINAL_REPERTOIRE);
+    assert_eq!(repertoire.len(), 1 << 7);
+
+    // after seven 15-bit characters, a final character makes up the last
+    // octet exactly, with no padding
+    for (value, &c) in repertoire.iter().enumerate() {
+        let text = format!("ҠҠҠҠҠҠҠ{}", c);
+        let mut expected = vec![0; 14];
+        expected[13] = value as u8;
+        assert_eq!(decode(&text, false).unwrap(), expected);
+        assert_eq!(encode(&expected, None), text);
+    }
+}
+
+#[test]
+fn round_trip() {
+    let encoded = check_round_trip(
+        |input| encode(input, None),
+        |text| decode(text, false),
+        |text, buf| decode_buf(text, buf, false),
+        |text, buf| decode_slice(text, buf, false),
+    );
+    for (len, encoded) in encoded.iter().enumerate() {
+        assert_eq!(encoded.chars().count(), (len * 8).div_ceil(15));
+    }
+}
+
+#[test]
+fn wrap() {
+    assert_eq!(encode("hello world", 3), "媒腻㐤\n┖ꈳ埳");
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtWith(4, "\r\n")),
+        "媒腻㐤┖\r\nꈳ埳"
+    );
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtUtf16Len(2)),
+        "媒腻\n㐤┖\nꈳ埳"
+    );
+    // the box drawing character is narrow, and the rest are wide
+    assert_eq!(
+        encode("hello world", WrapOptions::WrapAtDisplayWidth(5)),
+        "媒腻\n㐤┖ꈳ\n埳"
+    );
+
+    let mut buf = String::new();
+    encode_buf("hello world", &mut buf, 3);
+    assert_eq!(decode(&buf, true).unwrap(), b"hello world");
+    assert_eq!(
+        decode(&buf, false),
+        Err(Error::InvalidCodePoint("媒腻㐤".len(), '\n'))
+    );
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(decode("媒a", false), Err(Error::InvalidCodePoint(3, 'a')));
+    assert_eq!(decode("a媒腻㐤┖ꈳ埳", true).unwrap(), b"hello world");
+    // a final character before the end
+    assert_eq!(decode("ɟҠ", false), Err(Error::InvalidLength));
+    assert_eq!(decode("ɟ Ҡ", true), Err(Error::InvalidLength));
+    // leftover bits that aren't ones
+    assert_eq!(decode("Ҡ", false), Err(Error::InvalidPadding));
+    assert_eq!(decode("ƀ", false), Err(Error::InvalidPadding));
+}
@@ -0,0 +1,55 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode, encode, encode_prefix_within, Budget};
+
+fn every_block() -> Vec<u8> {
+    (0..=255).flat_map(|b| vec![b, b]).collect()
+}
+
+macro_rules! budget {
+    ( $name:ident, $variant:ident, $len:expr ) => {
+        #[test]
+        fn $name() {
+            let input = every_block();
+            for limit in 0..600 {
+                let (encoded, consumed) = encode_prefix_within(&input, Budget::$variant(limit));
+                assert!($len(&encoded) <= limit, "too long at {}", limit);
+                assert_eq!(decode(&encoded, false).unwrap(), &input[..consumed]);
+
+                // nothing more would have fit
+                if consumed < input.len() {
+                    let longer = encode(&input[..consumed + 1], None);
+                    assert!($len(&longer) > limit, "too short at {}", limit);
+                }
+            }
+        }
+    };
+}
+
+budget!(code_points, CodePoints, |s: &str| s.chars().count());
+budget!(utf8_len, Utf8Len, str::len);
+budget!(utf16_len, Utf16Len, |s: &str| s.encode_utf16().count());
+
+#[test]
+fn whole_input() {
+    assert_eq!(
+        encode_prefix_within("hello world", Budget::CodePoints(6)),
+        (encode("hello world", None), 11)
+    );
+    assert_eq!(
+        encode_prefix_within("", Budget::Utf8Len(0)),
+        (String::new(), 0)
+    );
+}
@@ -0,0 +1,139 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_checked, encode, encode_checked, CheckedError, Error, MAX_CHECKED_LEN};
+
+#[test]
+fn round_trip() {
+    for len in 0..50 {
+        let input: Vec<u8> = (0..len).map(|i| (i * 37) as u8).collect();
+        for wrap in &[None, Some(4)] {
+            let encoded = encode_checked(&input, *wrap).unwrap();
+            assert_eq!(decode_checked(&encoded, wrap.is_some()).unwrap(), input);
+        }
+    }
+}
+
+#[test]
+fn data_is_plain() {
+    let encoded = encode_checked("hello world", None).unwrap();
+    let data: String = encoded.chars().skip(3).collect();
+    assert_eq!(data, encode("hello world", None));
+}
+
+#[test]
+fn locates_single_substitution() {
+    for &input in &[&b""[..], b"a", b"hello world", b"hello world!"] {
+        let encoded = encode_checked(input, None).unwrap();
+        for (offset, original) in encoded.char_indices() {
+            let replacements = [
+                original as u32 ^ 1,
+                original as u32 ^ 0x80,
+                // another block entirely
+                0x4E00,
+                0x20000,
+                // the padding block
+                0x1500 + (original as u32 & 0xFF),
+            ];
+            for &replacement in &replacements {
+                let replacement = std::char::from_u32(replacement).unwrap();
+                if replacement == original {
+                    continue;
+                }
+
+                let mut damaged = encoded.clone();
+                damaged.replace_range(
+                    offset..offset + original.len_utf8(),
+                    &replacement.to_string(),
+                );
+                match decode_checked(&damaged, false) {
+                    Err(CheckedError::ChecksumMismatch(location)) => {
+                        assert_eq!(location, Some(offset), "{:?} at {}", input, offset)
+                    }
+                    // a padding character anywhere but the end isn't valid
+                    Err(CheckedError::Decode(Error::InvalidLength)) => {}
+                    Err(CheckedError::TooShort) => {}
+                    other => panic!("{:?} at {} undetected: {:?}", input, offset, other),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn locates_within_data() {
+    let encoded = encode_checked("hello world", None).unwrap();
+    let damaged = encoded.replace("啯", "啰");
+    assert_eq!(
+        decode_checked(&damaged, false),
+        Err(CheckedError::ChecksumMismatch(damaged.find('啰')))
+    );
+}
+
+#[test]
+fn locates_final_substitution() {
+    // replacing the final character may change whether the data looks padded
+    for len in 1..20 {
+        let input: Vec<u8> = (0..len).collect();
+        let encoded = encode_checked(&input, None).unwrap();
+        let (offset, original) = encoded.char_indices().last().unwrap();
+        for &replacement in &['㐀', '㐁', 'ᔀ', 'ᔁ', '𒁷'] {
+            if replacement == original {
+                continue;
+            }
+
+            let mut damaged = encoded.clone();
+            damaged.replace_range(offset.., &replacement.to_string());
+            assert_eq!(
+                decode_checked(&damaged, false),
+                Err(CheckedError::ChecksumMismatch(Some(offset))),
+                "{} octets, replaced with {}",
+                len,
+                replacement
+            );
+        }
+    }
+}
+
+#[test]
+fn detects_multiple_substitutions() {
+    let encoded = encode_checked("hello world", None).unwrap();
+    let damaged = encoded.replace("驨", "驩").replace("𒁷", "𒁸");
+    match decode_checked(&damaged, false) {
+        Err(CheckedError::ChecksumMismatch(_)) => {}
+        other => panic!("undetected: {:?}", other),
+    }
+}
+
+#[test]
+fn too_short() {
+    assert_eq!(decode_checked("", false), Err(CheckedError::TooShort));
+    assert_eq!(decode_checked("㐀㐀", false), Err(CheckedError::TooShort));
+    assert_eq!(decode_checked("㐀㐀ᔀ", false), Err(CheckedError::TooShort));
+}
+
+#[test]
+fn too_long() {
+    let input = vec![0x55; MAX_CHECKED_LEN];
+    let encoded = encode_checked(&input, None).unwrap();
+    assert_eq!(decode_checked(&encoded, false).unwrap(), input);
+
+    // a codeword of 65536 symbols would be longer than the code allows
+    assert_eq!(
+        encode_checked(&[0x55; MAX_CHECKED_LEN + 1][..], None),
+        Err(CheckedError::TooLong)
+    );
+    let longer = encoded + &encode(&[0x55], None);
+    assert_eq!(decode_checked(&longer, false), Err(CheckedError::TooLong));
+}
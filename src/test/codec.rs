@@ -0,0 +1,114 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::sample;
+use crate::codec::{AnyCodec, Base16384, Base2048, Base32768, Base65536, Codec, CodecError};
+use crate::{base16384, base2048, base32768, WrapOptions};
+
+const CODECS: &[AnyCodec] = &[
+    AnyCodec::Base65536,
+    AnyCodec::Base32768,
+    AnyCodec::Base2048,
+    AnyCodec::Base16384,
+];
+
+#[test]
+fn names() {
+    for &codec in CODECS {
+        assert_eq!(AnyCodec::from_name(codec.name()), Some(codec));
+        assert_eq!(
+            AnyCodec::from_name(&codec.name().to_uppercase()),
+            Some(codec)
+        );
+    }
+    assert_eq!(AnyCodec::from_name(""), None);
+    assert_eq!(AnyCodec::from_name("base64"), None);
+    assert_eq!(AnyCodec::default(), AnyCodec::Base65536);
+}
+
+#[test]
+fn same_as_modules() {
+    let input = b"hello world";
+    assert_eq!(
+        Base65536.encode(input, WrapOptions::NoWrap),
+        crate::encode(input, None)
+    );
+    assert_eq!(
+        Base32768.encode(input, 3.into()),
+        base32768::encode(input, 3)
+    );
+    assert_eq!(Base2048.encode(input, 3.into()), base2048::encode(input, 3));
+    assert_eq!(
+        Base16384.encode(input, 3.into()),
+        base16384::encode(input, 3)
+    );
+}
+
+#[test]
+fn lengths() {
+    for &codec in CODECS {
+        for len in 0..100usize {
+            let input = sample(len);
+            let encoded = codec.encode(&input, WrapOptions::NoWrap);
+            let chars = encoded.chars().count();
+            assert_eq!(codec.encoded_len(len), chars, "{} of {}", codec.name(), len);
+            assert!(
+                codec.decoded_len(chars) >= len,
+                "{} of {}",
+                codec.name(),
+                len
+            );
+
+            let mut buf = Vec::new();
+            codec.decode_buf(&encoded, &mut buf, false).unwrap();
+            assert_eq!(buf, input);
+        }
+    }
+}
+
+#[test]
+fn dynamic() {
+    let codecs: Vec<Box<dyn Codec>> = vec![
+        Box::new(Base65536),
+        Box::new(Base32768),
+        Box::new(Base2048),
+        Box::new(Base16384),
+    ];
+    for codec in &codecs {
+        let encoded = codec.encode(b"hello world", 4.into());
+        assert_eq!(codec.decode(&encoded, true).unwrap(), b"hello world");
+    }
+}
+
+#[test]
+fn errors() {
+    for &codec in CODECS {
+        assert_eq!(
+            codec.decode("!", false),
+            Err(CodecError::InvalidCodePoint(0, '!'))
+        );
+    }
+    assert_eq!(
+        AnyCodec::Base65536.decode("ᔀ㐀", false),
+        Err(CodecError::InvalidLength)
+    );
+    assert_eq!(
+        AnyCodec::Base2048.decode("8", false),
+        Err(CodecError::InvalidPadding)
+    );
+    assert_eq!(
+        AnyCodec::Base16384.decode("一", false),
+        Err(CodecError::InvalidLength)
+    );
+}
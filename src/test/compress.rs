@@ -0,0 +1,127 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    decode_auto, decode_auto_with_limit, encode, encode_compressed, CompressError, Compression,
+    Error,
+};
+
+fn codecs() -> Vec<Compression> {
+    vec![
+        #[cfg(feature = "deflate")]
+        Compression::Deflate,
+        #[cfg(feature = "zstd")]
+        Compression::Zstd,
+    ]
+}
+
+fn log() -> String {
+    (0..200)
+        .map(|i| format!("{{\"level\":\"info\",\"request\":{},\"status\":200}}\n", i))
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    for codec in codecs() {
+        for input in &[String::new(), "a".to_owned(), log()] {
+            let encoded = encode_compressed(input, codec, None);
+            assert!(encoded.starts_with(codec.tag()));
+            assert_eq!(decode_auto(&encoded, false).unwrap(), input.as_bytes());
+        }
+    }
+}
+
+#[test]
+fn compresses() {
+    let input = log();
+    for codec in codecs() {
+        let encoded = encode_compressed(&input, codec, None);
+        assert!(encoded.len() * 4 < encode(&input, None).len());
+    }
+}
+
+#[test]
+fn wrapped() {
+    let input = log();
+    for codec in codecs() {
+        let encoded = encode_compressed(&input, codec, 16);
+        assert!(encoded.contains('\n'));
+        assert!(decode_auto(&encoded, false).is_err());
+        assert_eq!(decode_auto(&encoded, true).unwrap(), input.as_bytes());
+        let indented = format!("\n  {}", encoded);
+        assert_eq!(decode_auto(&indented, true).unwrap(), input.as_bytes());
+        let quoted = format!("> {}", encoded);
+        assert_eq!(decode_auto(&quoted, true).unwrap(), input.as_bytes());
+    }
+}
+
+#[test]
+fn plain() {
+    for input in &[&b""[..], b"a", b"hello world"] {
+        assert_eq!(decode_auto(&encode(input, None), false).unwrap(), *input);
+        assert_eq!(decode_auto(&encode(input, 1), true).unwrap(), *input);
+    }
+    match decode_auto("驨ꍬ!", false) {
+        Err(CompressError::Decode(Error::InvalidCodePoint(6, '!'))) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn invalid() {
+    for codec in codecs() {
+        let tag = codec.tag();
+        match decode_auto(&format!("{}驨ꍬ!", tag), false) {
+            Err(CompressError::Decode(Error::InvalidCodePoint(offset, '!'))) => {
+                assert_eq!(offset, tag.len_utf8() + 6)
+            }
+            other => panic!("{:?}", other),
+        }
+        match decode_auto(&format!("{}驨ꍬ", tag), false) {
+            Err(CompressError::Decompress(_)) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+    match decode_auto("\u{2400}", false) {
+        Err(CompressError::UnknownCodec('\u{2400}')) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn limit() {
+    let input = log();
+    for codec in codecs() {
+        let encoded = encode_compressed(&input, codec, None);
+        assert_eq!(
+            decode_auto_with_limit(&encoded, false, input.len()).unwrap(),
+            input.as_bytes()
+        );
+        match decode_auto_with_limit(&encoded, false, input.len() - 1) {
+            Err(CompressError::TooLarge(limit)) => assert_eq!(limit, input.len() - 1),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    let encoded = encode("hello world", None);
+    assert_eq!(
+        decode_auto_with_limit(&encoded, false, 11).unwrap(),
+        b"hello world"
+    );
+    match decode_auto_with_limit(&encoded, false, 10) {
+        Err(CompressError::TooLarge(10)) => {}
+        other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_concatenated, decode_concatenated_iter, encode, Error};
+
+#[test]
+fn empty() {
+    assert_eq!(decode_concatenated("", false), Ok(Vec::new()));
+}
+
+#[test]
+fn single() {
+    let input = encode("hello world", None);
+    assert_eq!(
+        decode_concatenated(&input, false),
+        Ok(vec![b"hello world".to_vec()])
+    );
+}
+
+#[test]
+fn odd_lengths() {
+    let input = encode("a", None) + &encode("bcd", None) + &encode("efghi", None);
+    assert_eq!(
+        decode_concatenated(&input, false),
+        Ok(vec![b"a".to_vec(), b"bcd".to_vec(), b"efghi".to_vec()])
+    );
+}
+
+#[test]
+fn even_length_merges() {
+    let input = encode("ab", None) + &encode("c", None) + &encode("de", None);
+    assert_eq!(
+        decode_concatenated(&input, false),
+        Ok(vec![b"abc".to_vec(), b"de".to_vec()])
+    );
+}
+
+#[test]
+fn ignore_garbage() {
+    let input = encode("abc", None) + "\n" + &encode("def", None) + "\n";
+    assert_eq!(
+        decode_concatenated(&input, true),
+        Ok(vec![b"abc".to_vec(), b"def".to_vec()])
+    );
+    assert_eq!(
+        decode_concatenated(&input, false),
+        Err(Error::InvalidCodePoint(6, '\n'))
+    );
+}
+
+#[test]
+fn iter_fuses_after_error() {
+    let input = encode("abc", None) + "!" + &encode("def", None);
+    let mut messages = decode_concatenated_iter(&input, false);
+    assert_eq!(messages.next(), Some(Ok(b"abc".to_vec())));
+    assert_eq!(messages.next(), Some(Err(Error::InvalidCodePoint(6, '!'))));
+    assert_eq!(messages.next(), None);
+    assert_eq!(messages.next(), None);
+}
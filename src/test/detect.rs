@@ -0,0 +1,108 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{base16384, base2048, base32768, decode_any, detect, encode, Format};
+
+const INPUT: &[u8] = b"The quick brown fox jumps over the lazy dog.";
+
+fn formats(input: &str) -> Vec<Format> {
+    detect(input)
+        .into_iter()
+        .map(|(format, _)| format)
+        .collect()
+}
+
+#[test]
+fn unicode_formats() {
+    let encoded = [
+        (Format::Base65536, encode(INPUT, 10)),
+        (Format::Base32768, base32768::encode(INPUT, 10)),
+        (Format::Base2048, base2048::encode(INPUT, 10)),
+        (Format::Base16384, base16384::encode(INPUT, 10)),
+    ];
+    for (format, text) in &encoded {
+        let detected = detect(text);
+        assert_eq!(detected[0].0, *format, "{}", text);
+        assert!(detected[0].1 > 0.99, "{:?}", detected);
+        assert_eq!(decode_any(text), Some((*format, INPUT.to_vec())));
+    }
+}
+
+#[test]
+fn ascii_formats() {
+    let hex: String = INPUT.iter().map(|byte| format!("{:02x}", byte)).collect();
+    assert_eq!(decode_any(&hex), Some((Format::Hex, INPUT.to_vec())));
+    assert_eq!(
+        decode_any(&hex.to_uppercase()),
+        Some((Format::Hex, INPUT.to_vec()))
+    );
+
+    let base64 = "VGhlIHF1aWNrIGJyb3duIGZveCBqdW1wcyBvdmVyIHRoZSBsYXp5IGRvZy4=";
+    assert_eq!(formats(base64), vec![Format::Base64]);
+    assert_eq!(decode_any(base64), Some((Format::Base64, INPUT.to_vec())));
+    // without padding, wrapped, and URL-safe
+    assert_eq!(
+        decode_any("VGhlIHF1aWNrIGJyb3duIGZveCBq\ndW1wcyBvdmVyIHRoZSBsYXp5IGRvZy4"),
+        Some((Format::Base64, INPUT.to_vec()))
+    );
+    assert_eq!(
+        decode_any("-_-_"),
+        Some((Format::Base64, vec![0xFB, 0xFF, 0xBF]))
+    );
+}
+
+#[test]
+fn padding_rules() {
+    // base64 padding only at the end, and only to a multiple of four
+    assert_eq!(formats("QQ=="), vec![Format::Base64]);
+    assert!(formats("QQ=").is_empty());
+    assert!(formats("QQ==QQ==").is_empty());
+    assert!(formats("Q===").is_empty());
+    // leftover bits that no encoder would write
+    assert!(!formats("QR==").contains(&Format::Base64));
+
+    // a base65536 padding character only at the end
+    assert_eq!(formats("㘁ᔃ")[0], Format::Base65536);
+    assert!(formats("ᔃ㘁").is_empty());
+    // a base16384 tail marker only after a short group, although both are
+    // also base65536
+    assert_eq!(formats("乀渰㴃")[0], Format::Base16384);
+    assert_eq!(formats("㴃乀渰"), vec![Format::Base65536]);
+}
+
+#[test]
+fn ambiguous() {
+    let detected = detect("deadbeef");
+    let order: Vec<Format> = detected.iter().map(|&(format, _)| format).collect();
+    assert_eq!(order, vec![Format::Hex, Format::Base64, Format::Base2048]);
+    let total: f64 = detected.iter().map(|&(_, confidence)| confidence).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(detected.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+    // base16384 without a tail marker is also base65536, but it's much less
+    // likely for base65536 to stay within those blocks
+    let detected = detect(&base16384::encode(&[0; 7], None));
+    assert_eq!(detected[0].0, Format::Base16384);
+    assert_eq!(detected[1].0, Format::Base65536);
+    assert!(detected[0].1 > detected[1].1);
+}
+
+#[test]
+fn nothing() {
+    assert!(detect("").is_empty());
+    assert!(detect(" \n").is_empty());
+    assert!(detect("hello, world").is_empty());
+    assert_eq!(decode_any(""), None);
+    assert_eq!(decode_any("hello, world"), None);
+}
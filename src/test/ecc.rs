@@ -0,0 +1,179 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::encode;
+use crate::ecc::{decode, encode as encode_ecc, EccError, Params};
+
+// a simple deterministic generator, so that failures can be reproduced
+fn lcg(state: &mut u32) -> u32 {
+    *state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+    *state >> 8
+}
+
+fn damage(encoded: &str, positions: &[usize], erase: bool) -> String {
+    encoded
+        .chars()
+        .enumerate()
+        .map(|(index, code_point)| match index {
+            index if !positions.contains(&index) => code_point,
+            _ if erase => '\u{FFFD}',
+            // another base65536 character in the same block
+            _ => std::char::from_u32(code_point as u32 ^ 0x5A).unwrap(),
+        })
+        .collect()
+}
+
+#[test]
+fn params() {
+    assert!(Params::new(1, 1).is_ok());
+    assert!(Params::new(65_533, 1).is_ok());
+    assert_eq!(Params::new(0, 1), Err(EccError::InvalidParams));
+    assert_eq!(Params::new(1, 0), Err(EccError::InvalidParams));
+    assert_eq!(Params::new(65_534, 1), Err(EccError::InvalidParams));
+    assert_eq!(Params::new(usize::MAX, 1), Err(EccError::InvalidParams));
+}
+
+#[test]
+fn round_trip() {
+    let params = Params::new(5, 3).unwrap();
+    for len in 0..40 {
+        let input: Vec<u8> = (0..len).map(|i| (i * 71) as u8).collect();
+        let encoded = encode_ecc(&input, params, 4);
+        let decoded = decode(&encoded, params).unwrap();
+        assert_eq!(decoded.data, input);
+        assert!(decoded.corrected.is_empty());
+    }
+}
+
+#[test]
+fn data_is_plain() {
+    let params = Params::new(100, 4).unwrap();
+    let encoded = encode_ecc("hello world", params, None);
+    let data: String = encoded.chars().skip(4).collect();
+    assert_eq!(data, encode("hello world", None));
+}
+
+#[test]
+fn corrects_substitutions() {
+    let params = Params::new(8, 6).unwrap();
+    let mut state = 1;
+    for len in &[1, 2, 15, 16, 17, 40] {
+        let input: Vec<u8> = (0..*len).map(|_| lcg(&mut state) as u8).collect();
+        let encoded = encode_ecc(&input, params, None);
+        let offsets: Vec<usize> = encoded.char_indices().map(|(index, _)| index).collect();
+
+        // three errors in every block
+        for _ in 0..20 {
+            let mut positions = Vec::new();
+            for block in offsets.chunks(14).enumerate().map(|(index, _)| index * 14) {
+                let block_len = (offsets.len() - block).min(14);
+                while positions.iter().filter(|&&p| p >= block).count() < 3 {
+                    let position = block + lcg(&mut state) as usize % block_len;
+                    if !positions.contains(&position) {
+                        positions.push(position);
+                    }
+                }
+            }
+            positions.sort();
+
+            let damaged = damage(&encoded, &positions, false);
+            let decoded = decode(&damaged, params).unwrap();
+            assert_eq!(decoded.data, input);
+            let expected: Vec<usize> = damaged
+                .char_indices()
+                .enumerate()
+                .filter(|(index, _)| positions.contains(index))
+                .map(|(_, (offset, _))| offset)
+                .collect();
+            assert_eq!(decoded.corrected, expected);
+        }
+    }
+}
+
+#[test]
+fn corrects_erasures() {
+    let params = Params::new(8, 6).unwrap();
+    let encoded = encode_ecc("hello world, how are you?", params, None);
+    let damaged = damage(&encoded, &[0, 3, 6, 7, 8, 12, 14, 15, 16, 17, 18, 19], true);
+    assert_eq!(
+        &decode(&damaged, params).unwrap().data[..],
+        b"hello world, how are you?"
+    );
+}
+
+#[test]
+fn corrects_errors_and_erasures() {
+    let params = Params::new(8, 6).unwrap();
+    let encoded = encode_ecc("hello world, how are you?", params, None);
+    let damaged = damage(&encoded, &[1, 2, 3, 4], true);
+    let damaged = damage(&damaged, &[10], false);
+    let decoded = decode(&damaged, params).unwrap();
+    assert_eq!(&decoded.data[..], b"hello world, how are you?");
+    assert_eq!(decoded.corrected.len(), 5);
+}
+
+#[test]
+fn corrects_padding() {
+    // the final character says whether the data was padded, and changing it
+    // is a single error however it's changed
+    for &(data, parity) in &[(3, 2), (4, 4)] {
+        let params = Params::new(data, parity).unwrap();
+        for len in 1..20 {
+            let input: Vec<u8> = (0..len).collect();
+            let encoded = encode_ecc(&input, params, None);
+            let (offset, original) = encoded.char_indices().last().unwrap();
+            let last = original as u32 & 0xFF;
+            for &replacement in &[0x1500 + last, 0x1500 + (last ^ 1), 0x3400 + last, 0xFFFD] {
+                let replacement = std::char::from_u32(replacement).unwrap();
+                if replacement == original {
+                    continue;
+                }
+
+                let mut damaged = encoded.clone();
+                damaged.replace_range(offset.., &replacement.to_string());
+                let decoded = decode(&damaged, params).unwrap();
+                assert_eq!(decoded.data, input, "{} octets, {}", len, replacement);
+                assert_eq!(decoded.corrected, vec![offset]);
+            }
+        }
+    }
+}
+
+#[test]
+fn ignores_whitespace() {
+    let params = Params::new(3, 2).unwrap();
+    let encoded = encode_ecc("hello world", params, 2);
+    assert!(encoded.contains('\n'));
+    assert_eq!(&decode(&encoded, params).unwrap().data[..], b"hello world");
+}
+
+#[test]
+fn too_many_errors() {
+    let params = Params::new(8, 4).unwrap();
+    let encoded = encode_ecc("hello world", params, None);
+    let damaged = damage(&encoded, &[0, 1, 2, 3, 4], true);
+    assert_eq!(decode(&damaged, params), Err(EccError::Uncorrectable(0)));
+}
+
+#[test]
+fn invalid_length() {
+    let params = Params::new(4, 2).unwrap();
+    assert_eq!(decode("", params).unwrap().data, b"");
+    assert_eq!(decode("驨ꍬ", params), Err(EccError::InvalidLength));
+
+    // dropping characters can leave a final block without data
+    let encoded = encode_ecc("hello world!", params, None);
+    let damaged: String = encoded.chars().skip(2).collect();
+    assert_eq!(decode(&damaged, params), Err(EccError::InvalidLength));
+}
@@ -0,0 +1,98 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{encode, Error, LineDecoder, LineError};
+use std::io::{self, BufRead, Read};
+
+#[test]
+fn empty() {
+    assert!(LineDecoder::new(&b""[..]).next().is_none());
+}
+
+#[test]
+fn line_endings() {
+    let input = encode("foo", None) + "\n" + &encode("bar", None) + "\r\n\n" + &encode("baz", None);
+    let lines: Vec<_> = LineDecoder::new(input.as_bytes())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            b"foo".to_vec(),
+            b"bar".to_vec(),
+            Vec::new(),
+            b"baz".to_vec()
+        ]
+    );
+}
+
+#[test]
+fn trailing_carriage_return() {
+    let input = encode("foo", None) + "\n" + &encode("bar", None) + "\r";
+    let lines: Vec<_> = LineDecoder::new(input.as_bytes())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines, vec![b"foo".to_vec(), b"bar".to_vec()]);
+}
+
+#[test]
+fn bad_lines_continue() {
+    let mut input = encode("foo", None).into_bytes();
+    input.extend_from_slice(b" \n\xFF\n\r\r\n");
+    input.extend_from_slice(encode("bar", None).as_bytes());
+
+    let mut lines = LineDecoder::new(&input[..]);
+    match lines.next() {
+        Some(Err(LineError::Decode(1, Error::InvalidCodePoint(7, ' ')))) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    match lines.next() {
+        Some(Err(LineError::InvalidUtf8(2))) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    match lines.next() {
+        Some(Err(err @ LineError::Decode(3, Error::InvalidCodePoint(0, '\r')))) => {
+            assert_eq!(err.line(), 3)
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(lines.next().unwrap().unwrap(), b"bar");
+    assert!(lines.next().is_none());
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("failure"))
+    }
+}
+
+impl BufRead for FailingReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Err(io::Error::other("failure"))
+    }
+
+    fn consume(&mut self, _: usize) {}
+}
+
+#[test]
+fn io_error_ends() {
+    let mut lines = LineDecoder::new(FailingReader);
+    match lines.next() {
+        Some(Err(LineError::Io(1, _))) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(lines.next().is_none());
+}
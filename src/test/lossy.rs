@@ -0,0 +1,62 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_lossy, encode, Error};
+
+#[test]
+fn clean() {
+    let input = encode("hello world", None);
+    assert_eq!(decode_lossy(&input), (b"hello world".to_vec(), Vec::new()));
+}
+
+#[test]
+fn empty() {
+    assert_eq!(decode_lossy(""), (Vec::new(), Vec::new()));
+}
+
+#[test]
+fn garbage() {
+    let (buf, errors) = decode_lossy("a驨ꍬ\nb");
+    assert_eq!(buf, b"hell");
+    assert_eq!(
+        errors,
+        vec![
+            (0, Error::InvalidCodePoint(0, 'a')),
+            (4, Error::InvalidCodePoint(7, '\n')),
+            (4, Error::InvalidCodePoint(8, 'b')),
+        ]
+    );
+}
+
+#[test]
+fn padding_mid_stream() {
+    let (buf, errors) = decode_lossy("ᔁᔂᔃ㘁");
+    assert_eq!(buf, [1, 2, 3, 1, 2]);
+    assert_eq!(
+        errors,
+        vec![
+            (1, Error::InvalidLength),
+            (2, Error::InvalidLength),
+            (3, Error::InvalidLength),
+        ]
+    );
+}
+
+#[test]
+fn matches_decode() {
+    let input = encode(&[0, 255, 128, 7, 9], None);
+    let (buf, errors) = decode_lossy(&input);
+    assert_eq!(buf, super::decode(&input, false).unwrap());
+    assert!(errors.is_empty());
+}
@@ -14,16 +14,77 @@
 
 use super::*;
 
+mod alphabet;
+mod armor;
 mod bad;
+mod base16384;
+mod base2048;
+mod base32768;
+mod budget;
+mod checked;
+mod codec;
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+mod compress;
+mod concat;
+mod detect;
 mod doubled_bytes;
+mod ecc;
 mod ignore_garbage;
+mod lines;
+mod lossy;
+mod ordered;
 mod pairs;
+mod report;
 mod single_bytes;
+mod split;
+mod transcode;
+mod twitter;
+mod words;
 mod wrap;
+mod wrapped;
 
 #[cfg(feature = "nightly")]
 mod bench;
 
+// Octets for round-trip tests, which vary enough that every bit of every
+// character is used.
+fn sample(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 151 + 7) as u8).collect()
+}
+
+// Checks that samples of up to 64 octets survive encoding and each way of
+// decoding, and returns the encoding of each length for further checks.
+fn check_round_trip<Enc, Dec, Buf, Slice, Err>(
+    encode: Enc,
+    decode: Dec,
+    decode_buf: Buf,
+    decode_slice: Slice,
+) -> Vec<String>
+where
+    Enc: Fn(&[u8]) -> String,
+    Dec: Fn(&str) -> Result<Vec<u8>, Err>,
+    Buf: Fn(&str, &mut Vec<u8>) -> Result<(), Err>,
+    Slice: Fn(&str, &mut [u8]) -> Result<usize, Err>,
+    Err: std::fmt::Debug,
+{
+    (0..64)
+        .map(|len| {
+            let input = sample(len);
+            let encoded = encode(&input);
+            assert_eq!(decode(&encoded).unwrap(), input);
+
+            let mut buf = Vec::new();
+            decode_buf(&encoded, &mut buf).unwrap();
+            assert_eq!(buf, input);
+
+            let mut slice = [0; 64];
+            assert_eq!(decode_slice(&encoded, &mut slice).unwrap(), len);
+            assert_eq!(&slice[..len], &input[..]);
+            encoded
+        })
+        .collect()
+}
+
 #[test]
 fn correct_b2s_types() {
     #[cfg(feature = "fnv")]
@@ -55,3 +116,15 @@ fn wrapoptions_sync() {
     fn assert_sync<T: Sync>() {}
     assert_sync::<WrapOptions>();
 }
+
+#[test]
+fn wraperror_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<WrapError>();
+}
+
+#[test]
+fn wraperror_sync() {
+    fn assert_sync<T: Sync>() {}
+    assert_sync::<WrapError>();
+}
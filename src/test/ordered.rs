@@ -0,0 +1,125 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{check_round_trip, BLOCK_STARTS, PADDING_BLOCK_START};
+use crate::ordered::{decode, decode_buf, decode_slice, encode, encode_buf, WrapOptions};
+use crate::Error;
+use std::collections::HashSet;
+
+#[test]
+fn blocks_in_order() {
+    // the numbering of characters relies on this
+    assert!(PADDING_BLOCK_START < BLOCK_STARTS[0]);
+    assert!(BLOCK_STARTS.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn every_character() {
+    // every first octet, alone and with every second octet
+    let mut inputs: Vec<Vec<u8>> = Vec::new();
+    for byte1 in 0..=255u8 {
+        inputs.push(vec![byte1]);
+        for byte2 in 0..=255u8 {
+            inputs.push(vec![byte1, byte2]);
+        }
+    }
+    let encoded: Vec<String> = inputs.iter().map(|input| encode(input, None)).collect();
+
+    // in order, distinct, and all of base65536
+    assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+    let chars: HashSet<char> = encoded.iter().flat_map(|text| text.chars()).collect();
+    assert_eq!(chars.len(), 257 * 256);
+    assert!(encoded
+        .iter()
+        .all(|text| crate::decode(text, false).is_ok()));
+
+    for (input, text) in inputs.iter().zip(&encoded) {
+        assert_eq!(&decode(text, false).unwrap(), input);
+    }
+}
+
+#[test]
+fn order() {
+    let mut keys: Vec<Vec<u8>> = vec![vec![]];
+    for len in 1..6usize {
+        for seed in 0..200usize {
+            keys.push(
+                (0..len)
+                    .map(|i| ((seed * 37 + i * 101) % 7 * 42) as u8)
+                    .collect(),
+            );
+        }
+    }
+    keys.extend(vec![
+        vec![0],
+        vec![0, 0],
+        vec![0, 0, 0],
+        vec![255],
+        vec![255; 4],
+    ]);
+
+    let mut encoded: Vec<(String, &Vec<u8>)> =
+        keys.iter().map(|key| (encode(key, None), key)).collect();
+    encoded.sort();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    for (key, (text, original)) in sorted.iter().zip(&encoded) {
+        assert_eq!(key, *original, "{:?}", text);
+    }
+
+    // and the same by UTF-8 and UTF-32
+    assert!(encoded
+        .windows(2)
+        .all(|pair| pair[0].0.as_bytes() <= pair[1].0.as_bytes()));
+    assert!(encoded.windows(2).all(|pair| pair[0]
+        .0
+        .chars()
+        .map(u32::from)
+        .le(pair[1].0.chars().map(u32::from))));
+}
+
+#[test]
+fn round_trip() {
+    let encoded = check_round_trip(
+        |input| encode(input, None),
+        |text| decode(text, false),
+        |text, buf| decode_buf(text, buf, false),
+        |text, buf| decode_slice(text, buf, false),
+    );
+    for (len, encoded) in encoded.iter().enumerate() {
+        assert_eq!(encoded.chars().count(), len.div_ceil(2));
+    }
+}
+
+#[test]
+fn wrap() {
+    let input = [1; 8];
+    let mut buf = String::new();
+    encode_buf(&input[..], &mut buf, 3);
+    assert_eq!(buf, "㐃㐃㐃\n㐃");
+    assert_eq!(
+        encode(&input[..], WrapOptions::WrapAtDisplayWidth(5)),
+        "㐃㐃\n㐃㐃"
+    );
+    assert_eq!(decode(&buf, true).unwrap(), input);
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(decode("㐄!", false), Err(Error::InvalidCodePoint(3, '!')));
+    assert_eq!(decode("㐄!㘃", true).unwrap(), vec![1, 2, 3]);
+    // anything after a character ending the data
+    assert_eq!(decode("㘃㐄", false), Err(Error::InvalidLength));
+    assert_eq!(decode("ᔀ", false).unwrap(), vec![0]);
+}
@@ -0,0 +1,70 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_with_report, encode, Error, SkippedSpan};
+
+#[test]
+fn clean() {
+    let input = encode("hello world", None);
+    let (buf, report) = decode_with_report(&input, true).unwrap();
+    assert_eq!(buf, b"hello world");
+    assert!(report.is_clean());
+    assert_eq!(report.skipped(), 0);
+}
+
+#[test]
+fn spans() {
+    // "é" is two bytes long, so byte and character offsets diverge after it
+    let (buf, report) = decode_with_report("é㘁 x\u{4D00}ᔃ\n", true).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    assert_eq!(
+        report.spans,
+        vec![
+            SkippedSpan {
+                bytes: 0..2,
+                chars: 0..1,
+            },
+            SkippedSpan {
+                bytes: 5..10,
+                chars: 2..5,
+            },
+            SkippedSpan {
+                bytes: 13..14,
+                chars: 6..7,
+            },
+        ]
+    );
+    assert_eq!(report.whitespace, 2);
+    assert_eq!(report.ascii, 1);
+    assert_eq!(report.other, 2);
+    assert_eq!(report.skipped(), 5);
+}
+
+#[test]
+fn near_miss() {
+    // U+4D00 sits in the gap between the U+4C00 and U+4E00 blocks
+    assert!(decode_with_report("\u{4D00}", true).unwrap().1.near_miss);
+    // U+1400 is directly before the padding block
+    assert!(decode_with_report("\u{1400}", true).unwrap().1.near_miss);
+    assert!(!decode_with_report("é x", true).unwrap().1.near_miss);
+}
+
+#[test]
+fn strict() {
+    assert_eq!(
+        decode_with_report("㘁 ᔃ", false),
+        Err(Error::InvalidCodePoint(3, ' '))
+    );
+    assert!(decode_with_report("㘁ᔃ", false).unwrap().1.is_clean());
+}
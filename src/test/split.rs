@@ -0,0 +1,141 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode, encode, split_encode, Budget, Error, Reassembler, SplitError};
+
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 256) as u8).collect()
+}
+
+#[test]
+fn round_trip() {
+    for &len in &[0, 1, 2, 3, 100, 1001] {
+        for &budget in &[
+            Budget::CodePoints(7),
+            Budget::CodePoints(140),
+            Budget::Utf8Len(30),
+            Budget::Utf16Len(70),
+        ] {
+            let input = input(len);
+            let parts = split_encode(&input, budget).unwrap();
+
+            let mut reassembler = Reassembler::new();
+            for part in parts.iter().rev() {
+                reassembler.add(part).unwrap();
+            }
+            assert!(reassembler.is_complete());
+            assert_eq!(reassembler.finish().unwrap(), input, "{} {:?}", len, budget);
+        }
+    }
+}
+
+#[test]
+fn within_budget() {
+    let input = input(5000);
+    for limit in 27..60 {
+        for part in split_encode(&input, Budget::Utf8Len(limit)).unwrap() {
+            assert!(part.len() <= limit);
+        }
+        for part in split_encode(&input, Budget::Utf16Len(limit)).unwrap() {
+            assert!(part.encode_utf16().count() <= limit);
+        }
+    }
+}
+
+#[test]
+fn missing_and_duplicate() {
+    let parts = split_encode(&input(100), Budget::CodePoints(10)).unwrap();
+    assert_eq!(parts.len(), 13);
+
+    let mut reassembler = Reassembler::new();
+    assert_eq!(reassembler.missing(), Vec::<u16>::new());
+    assert!(!reassembler.is_complete());
+
+    for part in parts.iter().step_by(2) {
+        reassembler.add(part).unwrap();
+    }
+    assert_eq!(reassembler.add(&parts[4]), Err(SplitError::Duplicate(4)));
+    let missing: Vec<u16> = (1..13).step_by(2).collect();
+    assert_eq!(reassembler.missing(), missing);
+    assert_eq!(reassembler.finish(), Err(SplitError::Missing(missing)));
+
+    assert_eq!(
+        Reassembler::new().finish(),
+        Err(SplitError::Missing(Vec::new()))
+    );
+}
+
+#[test]
+fn bad_parts() {
+    let parts = split_encode(&input(100), Budget::CodePoints(10)).unwrap();
+    let other = split_encode(&input(99), Budget::CodePoints(10)).unwrap();
+
+    let mut reassembler = Reassembler::new();
+    reassembler.add(&parts[0]).unwrap();
+    match reassembler.add(&other[1]) {
+        Err(SplitError::WrongMessage(_)) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(
+        reassembler.add("!"),
+        Err(SplitError::Decode(Error::InvalidCodePoint(0, '!')))
+    );
+    assert_eq!(
+        reassembler.add(&encode(&[0; 11], None)),
+        Err(SplitError::InvalidHeader)
+    );
+}
+
+#[test]
+fn corrupt() {
+    let parts = split_encode(&input(100), Budget::CodePoints(10)).unwrap();
+    let mut part = decode(&parts[3], false).unwrap();
+    *part.last_mut().unwrap() ^= 1;
+
+    let mut reassembler = Reassembler::new();
+    for (index, part) in parts.iter().enumerate() {
+        if index != 3 {
+            reassembler.add(part).unwrap();
+        }
+    }
+    reassembler.add(&encode(&part, None)).unwrap();
+    assert_eq!(reassembler.finish(), Err(SplitError::Corrupt));
+}
+
+#[test]
+fn budget_too_small() {
+    assert_eq!(
+        split_encode(&input(10), Budget::CodePoints(6)),
+        Err(SplitError::BudgetTooSmall)
+    );
+    assert_eq!(
+        split_encode(&input(10), Budget::CodePoints(3)),
+        Err(SplitError::BudgetTooSmall)
+    );
+    assert_eq!(
+        split_encode(&input(10), Budget::Utf8Len(20)),
+        Err(SplitError::BudgetTooSmall)
+    );
+}
+
+#[test]
+fn too_many_parts() {
+    // each part holds two octets of data
+    let parts = split_encode(&input(65535 * 2), Budget::CodePoints(7)).unwrap();
+    assert_eq!(parts.len(), 65535);
+    assert_eq!(
+        split_encode(&input(65535 * 2 + 1), Budget::CodePoints(7)),
+        Err(SplitError::TooLong)
+    );
+}
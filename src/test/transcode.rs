@@ -0,0 +1,157 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::sample;
+use crate::{
+    decode, encode, transcode_from_base64, transcode_from_base64_io, transcode_to_base64,
+    transcode_to_base64_io, Error, TranscodeError,
+};
+use std::io::{self, Read};
+
+const VECTORS: &[(&[u8], &str)] = &[
+    (b"", ""),
+    (b"f", "Zg=="),
+    (b"fo", "Zm8="),
+    (b"foo", "Zm9v"),
+    (b"foob", "Zm9vYg=="),
+    (b"fooba", "Zm9vYmE="),
+    (b"foobar", "Zm9vYmFy"),
+    (b"\xFB\xFF\xBF", "+/+/"),
+];
+
+// A reader handing out a few bytes at a time, to split characters between
+// reads.
+struct Trickle<'a>(&'a [u8], usize);
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.1.min(buf.len()).min(self.0.len());
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn vectors() {
+    for &(bytes, base64) in VECTORS {
+        assert_eq!(transcode_from_base64(base64).unwrap(), encode(bytes, None));
+        assert_eq!(transcode_to_base64(&encode(bytes, None)).unwrap(), base64);
+    }
+}
+
+#[test]
+fn round_trip() {
+    for len in 0..100 {
+        let input = sample(len);
+        let base64 = transcode_to_base64(&encode(&input, None)).unwrap();
+        assert_eq!(base64.len(), len.div_ceil(3) * 4);
+        assert_eq!(
+            decode(&transcode_from_base64(&base64).unwrap(), false).unwrap(),
+            input
+        );
+    }
+}
+
+#[test]
+fn lenient_base64() {
+    let encoded = encode(b"\xFB\xFF\xBFfo", None);
+    // URL-safe, unpadded, and wrapped
+    assert_eq!(transcode_from_base64("-_-_Zm8").unwrap(), encoded);
+    assert_eq!(transcode_from_base64("+/+/\r\nZm8=\n").unwrap(), encoded);
+}
+
+#[test]
+fn invalid_base64() {
+    assert!(matches!(
+        transcode_from_base64("Zm9v!"),
+        Err(TranscodeError::InvalidBase64(4, '!'))
+    ));
+    // padding where it isn't allowed, and anything after it
+    assert!(matches!(
+        transcode_from_base64("Zm9v="),
+        Err(TranscodeError::InvalidBase64(4, '='))
+    ));
+    assert!(matches!(
+        transcode_from_base64("Zg==Zg=="),
+        Err(TranscodeError::InvalidBase64(4, 'Z'))
+    ));
+    assert!(matches!(
+        transcode_from_base64("Zg="),
+        Err(TranscodeError::InvalidBase64Padding)
+    ));
+    assert!(matches!(
+        transcode_from_base64("Zm9vY"),
+        Err(TranscodeError::InvalidBase64Padding)
+    ));
+    // bits left over that aren't zero
+    assert!(matches!(
+        transcode_from_base64("Zh=="),
+        Err(TranscodeError::InvalidBase64Padding)
+    ));
+}
+
+#[test]
+fn invalid_base65536() {
+    assert!(matches!(
+        transcode_to_base64("驨!"),
+        Err(TranscodeError::Decode(Error::InvalidCodePoint(3, '!')))
+    ));
+    assert!(matches!(
+        transcode_to_base64("ᔃ㘁"),
+        Err(TranscodeError::Decode(Error::InvalidLength))
+    ));
+}
+
+#[test]
+fn streaming() {
+    // much larger than the buffer, so that it takes many reads
+    let input = sample(100_000);
+    let base64 = transcode_to_base64(&encode(&input, None)).unwrap();
+
+    for &step in &[1, 7, 4096, usize::MAX] {
+        let mut encoded = Vec::new();
+        transcode_from_base64_io(Trickle(base64.as_bytes(), step), &mut encoded).unwrap();
+        let encoded = String::from_utf8(encoded).unwrap();
+        assert_eq!(encoded, encode(&input, None));
+
+        let mut output = Vec::new();
+        transcode_to_base64_io(Trickle(encoded.as_bytes(), step), &mut output).unwrap();
+        assert_eq!(output, base64.as_bytes());
+    }
+}
+
+#[test]
+fn streaming_errors() {
+    let mut output = Vec::new();
+    assert!(matches!(
+        transcode_to_base64_io(Trickle("驨ꍬ\u{FFFF}".as_bytes(), 2), &mut output),
+        Err(TranscodeError::Decode(Error::InvalidCodePoint(
+            6, '\u{FFFF}'
+        )))
+    ));
+    assert!(matches!(
+        transcode_to_base64_io(Trickle(b"\xE9\xA9\xA8\xFF", 1), &mut output),
+        Err(TranscodeError::InvalidUtf8(3))
+    ));
+    // a character cut off at the end
+    assert!(matches!(
+        transcode_to_base64_io(Trickle(b"\xE9\xA9\xA8\xE9\xA9", 1), &mut output),
+        Err(TranscodeError::InvalidUtf8(3))
+    ));
+    assert!(matches!(
+        transcode_from_base64_io(Trickle(b"Zm9v\nZm9v!", 3), &mut output),
+        Err(TranscodeError::InvalidBase64(9, '!'))
+    ));
+}
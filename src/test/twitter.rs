@@ -0,0 +1,44 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode, encode};
+use crate::twitter::{encode_for_tweet, max_bytes_per_tweet, weighted_len, MAX_WEIGHTED_LEN};
+
+#[test]
+fn weights() {
+    assert_eq!(weighted_len(""), 0);
+    assert_eq!(weighted_len("abc"), 3);
+    assert_eq!(weighted_len("ÀΩЖ"), 3);
+    assert_eq!(weighted_len("\u{2014}\u{201C}\u{2026}"), 4);
+    assert_eq!(weighted_len("日本語"), 6);
+    assert_eq!(weighted_len("\u{1F600}"), 2);
+}
+
+#[test]
+fn every_block_is_heavy() {
+    let input: Vec<u8> = (0..=255).flat_map(|b| vec![b, b]).chain(Some(0)).collect();
+    let encoded = encode(&input, None);
+    assert_eq!(weighted_len(&encoded), encoded.chars().count() * 2);
+}
+
+#[test]
+fn fills_tweet() {
+    for len in &[0, 1, 2, 279, 280, 281, 1000] {
+        let input: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+        let (tweet, consumed) = encode_for_tweet(&input);
+        assert_eq!(consumed, input.len().min(max_bytes_per_tweet()));
+        assert!(weighted_len(&tweet) <= MAX_WEIGHTED_LEN);
+        assert_eq!(decode(&tweet, false).unwrap(), &input[..consumed]);
+    }
+}
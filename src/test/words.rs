@@ -0,0 +1,76 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode, decode_to_u16s, encode, encode_u16s, Endian, Error};
+
+#[test]
+fn empty() {
+    assert_eq!(encode_u16s(&[], Endian::Little), "");
+    assert_eq!(decode_to_u16s("", Endian::Big), Ok(Vec::new()));
+}
+
+#[test]
+fn matches_octets() {
+    let words = [0x0000, 0x00FF, 0xFF00, 0xFFFF, 0x1234, 0xBEEF];
+    let little: Vec<u8> = words.iter().flat_map(|w: &u16| w.to_le_bytes()).collect();
+    let big: Vec<u8> = words.iter().flat_map(|w: &u16| w.to_be_bytes()).collect();
+
+    assert_eq!(encode_u16s(&words, Endian::Little), encode(&little, None));
+    assert_eq!(encode_u16s(&words, Endian::Big), encode(&big, None));
+    assert_eq!(
+        decode(&encode_u16s(&words, Endian::Big), false).unwrap(),
+        big
+    );
+}
+
+#[test]
+fn round_trip() {
+    let words: Vec<u16> = (0..=u16::MAX).collect();
+    for &endian in &[Endian::Little, Endian::Big] {
+        let encoded = encode_u16s(&words, endian);
+        assert_eq!(encoded.chars().count(), words.len());
+        assert_eq!(decode_to_u16s(&encoded, endian), Ok(words.clone()));
+    }
+}
+
+#[test]
+fn swapped() {
+    let encoded = encode_u16s(&[0x1234], Endian::Little);
+    assert_eq!(decode_to_u16s(&encoded, Endian::Big), Ok(vec![0x3412]));
+}
+
+#[test]
+fn trailing_padding() {
+    let encoded = encode(&[1, 2, 3], None);
+    assert_eq!(
+        decode_to_u16s(&encoded, Endian::Little),
+        Err(Error::InvalidLength)
+    );
+}
+
+#[test]
+fn padding_mid_stream() {
+    assert_eq!(
+        decode_to_u16s("ᔃ㘁", Endian::Little),
+        Err(Error::InvalidLength)
+    );
+}
+
+#[test]
+fn garbage() {
+    assert_eq!(
+        decode_to_u16s("㘁 㘁", Endian::Little),
+        Err(Error::InvalidCodePoint(3, ' '))
+    );
+}
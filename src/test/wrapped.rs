@@ -0,0 +1,350 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_wrapped, detect_wrap, encode, Error, WrapError, WrapOptions};
+
+const INPUT: &[u8] = b"The quick brown fox jumps over the lazy dog.";
+
+#[test]
+fn round_trip() {
+    for columns in 1..30 {
+        for wrap in &[
+            WrapOptions::WrapAt(columns),
+            WrapOptions::WrapAtWith(columns, "\r\n"),
+            WrapOptions::WrapAtWith(columns, " -- "),
+        ] {
+            let encoded = encode(INPUT, *wrap);
+            assert_eq!(
+                decode_wrapped(&encoded, *wrap).unwrap(),
+                INPUT,
+                "Failed at {:?}",
+                wrap
+            );
+        }
+    }
+}
+
+#[test]
+fn no_wrap() {
+    let encoded = encode(INPUT, None);
+    assert_eq!(decode_wrapped(&encoded, None).unwrap(), INPUT);
+    assert!(decode_wrapped(&(encoded + "\n"), None).is_err());
+}
+
+#[test]
+fn empty() {
+    assert_eq!(decode_wrapped("", 3), Ok(Vec::new()));
+    assert_eq!(detect_wrap(""), Some(WrapOptions::NoWrap));
+}
+
+#[test]
+fn misplaced_breaks() {
+    // too early
+    assert_eq!(
+        decode_wrapped("驨ꍬ\n啯𒁷ꍲᕤ", 3),
+        Err(Error::InvalidCodePoint(6, '\n'))
+    );
+    // too late
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯𒁷\nꍲᕤ", 3),
+        Err(Error::InvalidCodePoint(9, '𒁷'))
+    );
+    // trailing
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯\n𒁷ꍲᕤ\n", 3),
+        Err(Error::InvalidCodePoint(20, '\n'))
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯\n", 3),
+        Err(Error::InvalidCodePoint(9, '\n'))
+    );
+}
+
+#[test]
+fn wrong_eol() {
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯\r\n𒁷ꍲᕤ", 3),
+        Err(Error::InvalidCodePoint(9, '\r'))
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯\n𒁷ꍲᕤ", WrapOptions::WrapAtWith(3, "\r\n")),
+        Err(Error::InvalidCodePoint(9, '\n'))
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯\r𒁷ꍲᕤ", WrapOptions::WrapAtWith(3, "\r\n")),
+        Err(Error::InvalidCodePoint(10, '𒁷'))
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ啯\r", WrapOptions::WrapAtWith(3, "\r\n")),
+        Err(Error::InvalidCodePoint(9, '\r'))
+    );
+}
+
+#[test]
+fn padding() {
+    assert_eq!(decode_wrapped("ᔁ\n㘁", 1), Err(Error::InvalidLength));
+}
+
+#[test]
+#[should_panic]
+fn zero_columns() {
+    let _ = decode_wrapped("㘁", 0);
+}
+
+#[test]
+fn detect() {
+    for columns in 1..30 {
+        for wrap in &[
+            WrapOptions::WrapAt(columns),
+            WrapOptions::WrapAtWith(columns, "\r\n"),
+        ] {
+            let encoded = encode(INPUT, *wrap);
+            let expected = if columns >= INPUT.len().div_ceil(2) {
+                WrapOptions::NoWrap
+            } else {
+                *wrap
+            };
+            assert_eq!(detect_wrap(&encoded), Some(expected));
+        }
+    }
+}
+
+#[test]
+fn detect_garbage() {
+    assert_eq!(detect_wrap("\n驨ꍬ啯"), None);
+    assert_eq!(detect_wrap("驨ꍬ\n啯\n"), None);
+    assert_eq!(detect_wrap("ᔁ㘁"), None);
+}
+
+#[test]
+fn formatted_round_trip() {
+    for columns in 1..30 {
+        for &(eol, prefix) in &[("\n", "// "), ("\r\n", "> "), ("\n", "")] {
+            let wrap = WrapOptions::new(columns, eol)
+                .unwrap()
+                .with_prefix(prefix)
+                .unwrap();
+            for wrap in &[wrap, wrap.with_trailing_eol().unwrap()] {
+                let encoded = encode(INPUT, *wrap);
+                assert_eq!(
+                    decode_wrapped(&encoded, *wrap).unwrap(),
+                    INPUT,
+                    "Failed at {:?}",
+                    wrap
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn formatted_encode() {
+    let wrap = WrapOptions::WrapAt(2).with_prefix("> ").unwrap();
+    assert_eq!(encode("hello", wrap), "> 驨ꍬ\n> ᕯ");
+    assert_eq!(
+        encode("hello", wrap.with_trailing_eol().unwrap()),
+        "> 驨ꍬ\n> ᕯ\n"
+    );
+    assert_eq!(encode("", wrap.with_trailing_eol().unwrap()), "");
+
+    let wrap = WrapOptions::NoWrap.with_prefix("# ").unwrap();
+    assert_eq!(encode("hello", wrap), "# 驨ꍬᕯ");
+    assert_eq!(decode_wrapped("# 驨ꍬᕯ", wrap).unwrap(), b"hello");
+}
+
+#[test]
+fn formatted_validation() {
+    assert_eq!(WrapOptions::new(0, "\n"), Err(WrapError::ZeroColumns));
+    assert_eq!(WrapOptions::new(1, "\nᔀ"), Err(WrapError::InvalidEol('ᔀ')));
+    assert_eq!(
+        WrapOptions::WrapAt(1).with_prefix("㐀"),
+        Err(WrapError::InvalidPrefix('㐀'))
+    );
+    assert_eq!(
+        WrapOptions::WrapAt(0).with_prefix(""),
+        Err(WrapError::ZeroColumns)
+    );
+    assert_eq!(
+        WrapOptions::WrapAt(0).with_trailing_eol(),
+        Err(WrapError::ZeroColumns)
+    );
+}
+
+#[test]
+fn formatted_trailing_eol() {
+    let wrap = WrapOptions::WrapAt(2).with_prefix("> ").unwrap();
+    assert_eq!(
+        decode_wrapped("> 驨ꍬ\n> ᕯ\n", wrap),
+        Err(Error::InvalidCodePoint(14, '\n'))
+    );
+
+    let wrap = wrap.with_trailing_eol().unwrap();
+    assert_eq!(decode_wrapped("> 驨ꍬ\n> ᕯ\n", wrap).unwrap(), b"hello");
+    assert_eq!(decode_wrapped("> 驨ꍬ\n> ᕯ", wrap).unwrap(), b"hello");
+    assert_eq!(decode_wrapped("> 驨ꍬ\n", wrap).unwrap(), b"hell");
+    assert_eq!(
+        decode_wrapped("> 驨ꍬ\n> ᕯ\n\n", wrap),
+        Err(Error::InvalidCodePoint(15, '\n'))
+    );
+}
+
+#[test]
+fn formatted_bad_prefix() {
+    let wrap = WrapOptions::WrapAt(2).with_prefix("> ").unwrap();
+    assert_eq!(
+        decode_wrapped("> 驨ꍬ\n>ᕯ", wrap),
+        Err(Error::InvalidCodePoint(10, 'ᕯ'))
+    );
+    assert_eq!(
+        decode_wrapped("> 驨ꍬ\n>", wrap),
+        Err(Error::InvalidCodePoint(9, '>'))
+    );
+    assert_eq!(
+        decode_wrapped("> 驨ꍬ\n> ", wrap),
+        Err(Error::InvalidCodePoint(9, '>'))
+    );
+    assert_eq!(
+        decode_wrapped("> 驨ꍬ\n>  ᕯ", wrap),
+        Err(Error::InvalidCodePoint(11, ' '))
+    );
+}
+
+fn display_width(code_point: char) -> usize {
+    match code_point as u32 {
+        0x1500..=0x15FF | 0xA500..=0x1FFFF => 1,
+        _ => 2,
+    }
+}
+
+#[test]
+fn display_width_encode() {
+    let input: Vec<u8> = (0..=255).flat_map(|b| vec![b, b]).chain(Some(0)).collect();
+    for columns in 1..40 {
+        let encoded = encode(&input, WrapOptions::WrapAtDisplayWidth(columns));
+        let lines: Vec<_> = encoded.split('\n').collect();
+        for (i, line) in lines.iter().enumerate() {
+            let width: usize = line.chars().map(display_width).sum();
+            assert!(
+                width <= columns || line.chars().count() == 1,
+                "line {} too wide at {}",
+                i,
+                columns
+            );
+
+            if let Some(next) = lines.get(i + 1) {
+                let next = display_width(next.chars().next().unwrap());
+                assert!(
+                    width + next > columns,
+                    "line {} too short at {}",
+                    i,
+                    columns
+                );
+            }
+        }
+
+        assert_eq!(
+            decode_wrapped(&encoded, WrapOptions::WrapAtDisplayWidth(columns)).unwrap(),
+            input,
+            "Failed at {}",
+            columns
+        );
+    }
+}
+
+#[test]
+fn display_width_decode() {
+    let wrap = WrapOptions::WrapAtDisplayWidth(5);
+    // U+10600 and U+1500 are a single cell wide, all others are two
+    assert_eq!(
+        decode_wrapped("驨ꍬ𐘀\n驨ᔀ", wrap).unwrap(),
+        [104, 101, 108, 108, 0, 110, 104, 101, 0]
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ\nᔀ", wrap),
+        Err(Error::InvalidCodePoint(6, '\n'))
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ\n𐘀", wrap),
+        Err(Error::InvalidCodePoint(6, '\n'))
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ𐘀驨", wrap),
+        Err(Error::InvalidCodePoint(10, '驨'))
+    );
+    assert_eq!(decode_wrapped("驨ꍬ\n驨", wrap).unwrap(), b"hellhe");
+
+    let wrap = WrapOptions::WrapAtDisplayWidth(1);
+    assert_eq!(encode("hello", wrap), "驨\nꍬ\nᕯ");
+    assert_eq!(decode_wrapped("驨\nꍬ\nᕯ", wrap).unwrap(), b"hello");
+}
+
+macro_rules! encoded_len_wrap {
+    ( $name:ident, $variant:ident, $len:expr ) => {
+        #[test]
+        fn $name() {
+            let input: Vec<u8> = (0..=255).flat_map(|b| vec![b, b]).chain(Some(0)).collect();
+            for columns in 1..40 {
+                let wrap = WrapOptions::$variant(columns);
+                let encoded = encode(&input, wrap);
+                let lines: Vec<_> = encoded.split('\n').collect();
+                for (i, line) in lines.iter().enumerate() {
+                    let len: usize = line.chars().map($len).sum();
+                    assert!(
+                        len <= columns || line.chars().count() == 1,
+                        "line {} too long at {}",
+                        i,
+                        columns
+                    );
+
+                    if let Some(next) = lines.get(i + 1) {
+                        let next = $len(next.chars().next().unwrap());
+                        assert!(len + next > columns, "line {} too short at {}", i, columns);
+                    }
+                }
+
+                assert_eq!(
+                    decode_wrapped(&encoded, wrap).unwrap(),
+                    input,
+                    "Failed at {}",
+                    columns
+                );
+            }
+        }
+    };
+}
+
+encoded_len_wrap!(utf8_len, WrapAtUtf8Len, char::len_utf8);
+encoded_len_wrap!(utf16_len, WrapAtUtf16Len, char::len_utf16);
+
+#[test]
+fn encoded_len_with_eol() {
+    let wrap = WrapOptions::WrapAtUtf8Len(7).with_eol("\r\n").unwrap();
+    // U+12077 takes up four bytes, U+9A68 and U+A36C three
+    assert_eq!(encode("hello world", wrap), "驨ꍬ\r\n啯𒁷\r\nꍲᕤ");
+    assert_eq!(
+        decode_wrapped("驨ꍬ\r\n啯𒁷\r\nꍲᕤ", wrap).unwrap(),
+        b"hello world"
+    );
+    assert_eq!(
+        decode_wrapped("驨ꍬ\r\n啯\r\n𒁷ꍲ\r\nᕤ", wrap),
+        Err(Error::InvalidCodePoint(11, '\r'))
+    );
+
+    let wrap = WrapOptions::WrapAtUtf16Len(3);
+    assert_eq!(encode("hello world", wrap), "驨ꍬ啯\n𒁷ꍲ\nᕤ");
+    assert_eq!(
+        WrapOptions::WrapAt(3).with_eol("ᔀ"),
+        Err(WrapError::InvalidEol('ᔀ'))
+    );
+}
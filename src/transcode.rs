@@ -0,0 +1,374 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::base64;
+use crate::{decode_code_point, encode_code_point, Error};
+use std::io::{self, Read, Write};
+use std::{error, fmt, str};
+
+// The size of the buffer reads go into.
+const BUFFER_SIZE: usize = 8 * 1024;
+
+#[derive(Debug)]
+/// Represents an error while transcoding.
+///
+/// Used with [`transcode_from_base64`], [`transcode_to_base64`], and their
+/// streaming forms. See them for examples.
+///
+/// [`transcode_from_base64`]: fn.transcode_from_base64.html
+/// [`transcode_to_base64`]: fn.transcode_to_base64.html
+pub enum TranscodeError {
+    /// Reading or writing failed.
+    Io(io::Error),
+    /// The input was not valid UTF-8.
+    ///
+    /// Contains the offset from the beginning of the stream at which the
+    /// invalid UTF-8 starts.
+    InvalidUtf8(usize),
+    /// A character not valid in base64, or a misplaced padding character, was
+    /// found in the input stream.
+    ///
+    /// Contains the offset from the beginning of the stream at which the
+    /// invalid code point was found, and the actual code point.
+    InvalidBase64(usize, char),
+    /// The base64 ended part of the way through a character, its padding
+    /// didn't make up a group of four characters, or its final bits weren't
+    /// zero.
+    InvalidBase64Padding,
+    /// The input was not valid base65536. Offsets within the contained error
+    /// are from the beginning of the stream.
+    Decode(Error),
+}
+
+impl fmt::Display for TranscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TranscodeError::Io(ref err) => write!(f, "{}", err),
+            TranscodeError::InvalidUtf8(offset) => write!(f, "invalid utf-8 at offset {}", offset),
+            TranscodeError::InvalidBase64(offset, ch) => {
+                write!(f, "invalid base64 code point '{}' at offset {}", ch, offset)
+            }
+            TranscodeError::InvalidBase64Padding => write!(f, "base64 has invalid padding"),
+            TranscodeError::Decode(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for TranscodeError {
+    fn description(&self) -> &str {
+        match *self {
+            TranscodeError::Io(_) => "i/o error",
+            TranscodeError::InvalidUtf8(_) => "invalid utf-8",
+            TranscodeError::InvalidBase64(_, _) => "invalid base64",
+            TranscodeError::InvalidBase64Padding => "invalid base64 padding",
+            TranscodeError::Decode(_) => "invalid base65536",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            TranscodeError::Io(ref err) => Some(err),
+            TranscodeError::Decode(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TranscodeError {
+    fn from(err: io::Error) -> Self {
+        TranscodeError::Io(err)
+    }
+}
+
+// A transcoder fed with text a piece at a time, holding only the few octets
+// that don't yet make up a whole output character.
+trait Transcoder: Default {
+    fn push(&mut self, input: &str, offset: usize, buf: &mut String) -> Result<(), TranscodeError>;
+
+    fn finish(self, buf: &mut String) -> Result<(), TranscodeError>;
+}
+
+#[derive(Default)]
+struct FromBase64 {
+    decoder: base64::Decoder,
+    byte1: Option<u8>,
+}
+
+impl Transcoder for FromBase64 {
+    fn push(&mut self, input: &str, offset: usize, buf: &mut String) -> Result<(), TranscodeError> {
+        for (index, code_point) in input.char_indices() {
+            if code_point.is_whitespace() {
+                continue;
+            }
+
+            let byte = match self.decoder.push(code_point) {
+                Ok(Some(byte)) => byte,
+                Ok(None) => continue,
+                Err(_) => return Err(TranscodeError::InvalidBase64(offset + index, code_point)),
+            };
+            match self.byte1.take() {
+                Some(byte1) => buf.push(encode_code_point(byte1, Some(byte))),
+                None => self.byte1 = Some(byte),
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, buf: &mut String) -> Result<(), TranscodeError> {
+        self.decoder
+            .finish()
+            .map_err(|_| TranscodeError::InvalidBase64Padding)?;
+        if let Some(byte1) = self.byte1 {
+            buf.push(encode_code_point(byte1, None));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct ToBase64 {
+    encoder: base64::Encoder,
+    done: bool,
+}
+
+impl Transcoder for ToBase64 {
+    fn push(&mut self, input: &str, offset: usize, buf: &mut String) -> Result<(), TranscodeError> {
+        for (index, code_point) in input.char_indices() {
+            match decode_code_point(code_point) {
+                Some(_) if self.done => return Err(TranscodeError::Decode(Error::InvalidLength)),
+                Some((byte1, byte2)) => {
+                    self.encoder.push(byte1, buf);
+                    match byte2 {
+                        Some(byte2) => self.encoder.push(byte2, buf),
+                        None => self.done = true,
+                    }
+                }
+                None if code_point.is_whitespace() => {}
+                None => {
+                    let err = Error::InvalidCodePoint(offset + index, code_point);
+                    return Err(TranscodeError::Decode(err));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, buf: &mut String) -> Result<(), TranscodeError> {
+        self.encoder.finish(buf);
+        Ok(())
+    }
+}
+
+fn transcode<T: Transcoder>(input: &str, capacity: usize) -> Result<String, TranscodeError> {
+    let mut transcoder = T::default();
+    let mut output = String::with_capacity(capacity);
+    transcoder.push(input, 0, &mut output)?;
+    transcoder.finish(&mut output)?;
+    Ok(output)
+}
+
+fn transcode_io<T, R, W>(mut reader: R, mut writer: W) -> Result<(), TranscodeError>
+where
+    T: Transcoder,
+    R: Read,
+    W: Write,
+{
+    let mut transcoder = T::default();
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut output = String::with_capacity(BUFFER_SIZE * 2);
+    // the bytes of an incomplete character carried over from the last read,
+    // and the offset in the stream of the start of the buffer
+    let (mut carried, mut offset) = (0, 0);
+
+    loop {
+        let read = match reader.read(&mut buffer[carried..]) {
+            Ok(read) => read,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(TranscodeError::Io(err)),
+        };
+        let filled = carried + read;
+        if read == 0 {
+            if carried > 0 {
+                return Err(TranscodeError::InvalidUtf8(offset));
+            }
+            break;
+        }
+
+        let valid = match str::from_utf8(&buffer[..filled]) {
+            Ok(_) => filled,
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(TranscodeError::InvalidUtf8(offset + err.valid_up_to())),
+        };
+        // It is safe to unwrap because this prefix was just checked.
+        let text = str::from_utf8(&buffer[..valid]).unwrap();
+
+        transcoder.push(text, offset, &mut output)?;
+        writer.write_all(output.as_bytes())?;
+        output.clear();
+
+        buffer.copy_within(valid..filled, 0);
+        carried = filled - valid;
+        offset += valid;
+    }
+
+    transcoder.finish(&mut output)?;
+    writer.write_all(output.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Transcode base64 directly to base65536, without decoding all of the data
+/// at once.
+///
+/// Either the standard or URL-safe base64 alphabet may be used, with or
+/// without padding, and whitespace is skipped, so wrapped base64 is fine.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::InvalidBase64`] if a character isn't base64, or
+/// is a padding character where one isn't allowed, and
+/// [`TranscodeError::InvalidBase64Padding`] if the base64 doesn't end where
+/// it could have.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::transcode_from_base64;
+///
+/// assert_eq!("驨ꍬ啯𒁷ꍲᕤ", transcode_from_base64("aGVsbG8gd29ybGQ=")?);
+/// assert_eq!("驨ꍬ啯𒁷ꍲᕤ", transcode_from_base64("aGVsbG8g\nd29ybGQ")?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`TranscodeError::InvalidBase64`]: enum.TranscodeError.html#variant.InvalidBase64
+/// [`TranscodeError::InvalidBase64Padding`]: enum.TranscodeError.html#variant.InvalidBase64Padding
+pub fn transcode_from_base64<T>(input: &T) -> Result<String, TranscodeError>
+where
+    T: ?Sized + AsRef<str>,
+{
+    // every four characters become three octets, or one and a half
+    // characters of up to four bytes each
+    let input = input.as_ref();
+    transcode::<FromBase64>(input, input.len() / 4 * 6 + 4)
+}
+
+/// Transcode base65536 directly to standard, padded base64, without decoding
+/// all of the data at once.
+///
+/// Whitespace in the input is skipped.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::Decode`] if the input isn't valid base65536,
+/// with the same errors as [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::transcode_to_base64;
+///
+/// assert_eq!("aGVsbG8gd29ybGQ=", transcode_to_base64("驨ꍬ啯\n𒁷ꍲᕤ")?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`TranscodeError::Decode`]: enum.TranscodeError.html#variant.Decode
+/// [`decode`]: fn.decode.html
+pub fn transcode_to_base64<T>(input: &T) -> Result<String, TranscodeError>
+where
+    T: ?Sized + AsRef<str>,
+{
+    // every character of at least three bytes becomes two octets, or about
+    // three characters of base64
+    let input = input.as_ref();
+    transcode::<ToBase64>(input, input.len() + 4)
+}
+
+/// Transcode base64 from a reader to base65536 in a writer, through a fixed
+/// size buffer, so that input larger than memory can be transcoded.
+///
+/// The input must be UTF-8, and is otherwise read the same way as by
+/// [`transcode_from_base64`]. The output is written as UTF-8, and the writer
+/// is flushed at the end.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::Io`] if reading or writing fails, and
+/// [`TranscodeError::InvalidUtf8`] if the input isn't UTF-8. Otherwise, the
+/// errors are the same as those of [`transcode_from_base64`]. Some output may
+/// have been written before an error is found.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::transcode_from_base64_io;
+///
+/// let mut output = Vec::new();
+/// transcode_from_base64_io(&b"aGVsbG8gd29ybGQ=\n"[..], &mut output)?;
+/// assert_eq!("驨ꍬ啯𒁷ꍲᕤ", String::from_utf8(output)?);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`transcode_from_base64`]: fn.transcode_from_base64.html
+/// [`TranscodeError::Io`]: enum.TranscodeError.html#variant.Io
+/// [`TranscodeError::InvalidUtf8`]: enum.TranscodeError.html#variant.InvalidUtf8
+pub fn transcode_from_base64_io<R, W>(reader: R, writer: W) -> Result<(), TranscodeError>
+where
+    R: Read,
+    W: Write,
+{
+    transcode_io::<FromBase64, R, W>(reader, writer)
+}
+
+/// Transcode base65536 from a reader to base64 in a writer, through a fixed
+/// size buffer, so that input larger than memory can be transcoded.
+///
+/// The input must be UTF-8, and is otherwise read the same way as by
+/// [`transcode_to_base64`]. The writer is flushed at the end.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::Io`] if reading or writing fails, and
+/// [`TranscodeError::InvalidUtf8`] if the input isn't UTF-8. Otherwise, the
+/// errors are the same as those of [`transcode_to_base64`]. Some output may
+/// have been written before an error is found.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::transcode_to_base64_io;
+///
+/// let mut output = Vec::new();
+/// transcode_to_base64_io("驨ꍬ啯𒁷ꍲᕤ\n".as_bytes(), &mut output)?;
+/// assert_eq!(b"aGVsbG8gd29ybGQ=", &output[..]);
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`transcode_to_base64`]: fn.transcode_to_base64.html
+/// [`TranscodeError::Io`]: enum.TranscodeError.html#variant.Io
+/// [`TranscodeError::InvalidUtf8`]: enum.TranscodeError.html#variant.InvalidUtf8
+pub fn transcode_to_base64_io<R, W>(reader: R, writer: W) -> Result<(), TranscodeError>
+where
+    R: Read,
+    W: Write,
+{
+    transcode_io::<ToBase64, R, W>(reader, writer)
+}
@@ -0,0 +1,123 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for fitting base65536 into tweets.
+//!
+//! Twitter doesn't count every character equally. Following [twitter-text],
+//! characters in a few ranges (mostly Latin, Greek, Cyrillic, and common
+//! punctuation) count once towards the limit, and every other character,
+//! including every base65536 character, counts twice.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use base65536::twitter;
+//!
+//! let (tweet, consumed) = twitter::encode_for_tweet(&[0; 1024][..]);
+//! assert_eq!(twitter::max_bytes_per_tweet(), consumed);
+//! assert_eq!(twitter::MAX_WEIGHTED_LEN, twitter::weighted_len(&tweet));
+//! ```
+//!
+//! [twitter-text]: https://github.com/twitter/twitter-text
+
+use crate::encode_code_point;
+
+/// The maximum weighted length of a tweet.
+pub const MAX_WEIGHTED_LEN: usize = 280;
+
+// the weights below are in hundredths, as in twitter-text's configuration
+const SCALE: u32 = 100;
+const DEFAULT_WEIGHT: u32 = 200;
+const RANGES: &[(u32, u32, u32)] = &[
+    (0x0000, 0x10FF, 100),
+    (0x2000, 0x200D, 100),
+    (0x2010, 0x201F, 100),
+    (0x2032, 0x2037, 100),
+];
+
+fn weight(code_point: char) -> u32 {
+    let code_point = code_point as u32;
+    RANGES
+        .iter()
+        .find(|&&(start, end, _)| start <= code_point && code_point <= end)
+        .map_or(DEFAULT_WEIGHT, |&(_, _, weight)| weight)
+}
+
+/// Calculates the weighted length of a string, as counted by Twitter.
+///
+/// This applies the character weights of twitter-text's default
+/// configuration. It doesn't account for URLs, which Twitter counts as a
+/// fixed length, nor for emoji sequences, which Twitter counts as two no
+/// matter how many characters they're made up of; neither ever appears in
+/// base65536.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::twitter::weighted_len;
+///
+/// assert_eq!(11, weighted_len("hello world"));
+/// assert_eq!(12, weighted_len("驨ꍬ啯𒁷ꍲᕤ"));
+/// ```
+pub fn weighted_len(input: &str) -> usize {
+    let weight: u32 = input.chars().map(weight).sum();
+    (weight / SCALE) as usize
+}
+
+/// The maximum number of octets that can be encoded into a single tweet.
+///
+/// Every base65536 character has the same weight, so this doesn't depend on
+/// the input.
+pub fn max_bytes_per_tweet() -> usize {
+    (MAX_WEIGHTED_LEN * SCALE as usize / DEFAULT_WEIGHT as usize) * 2
+}
+
+/// Encode as many octets from the start of the input as will fit in a single
+/// tweet, as base65536.
+///
+/// Returns the encoded string, and the number of octets of input that it
+/// contains. Call this again with the rest of the input to fill further
+/// tweets.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::twitter::encode_for_tweet;
+///
+/// let input = vec![0; 1000];
+/// let (tweet, consumed) = encode_for_tweet(&input);
+/// assert_eq!(280, consumed);
+/// assert_eq!(input[..consumed], base65536::decode(&tweet, false).unwrap()[..]);
+/// ```
+pub fn encode_for_tweet<T>(input: &T) -> (String, usize)
+where
+    T: ?Sized + AsRef<[u8]>,
+{
+    let mut output = String::new();
+    let mut remaining = MAX_WEIGHTED_LEN as u32 * SCALE;
+    let mut consumed = 0;
+    for bytes in input.as_ref().chunks(2) {
+        let code_point = encode_code_point(bytes[0], bytes.get(1).cloned());
+        let weight = weight(code_point);
+        if weight > remaining {
+            break;
+        }
+
+        output.push(code_point);
+        remaining -= weight;
+        consumed += bytes.len();
+    }
+
+    (output, consumed)
+}
@@ -0,0 +1,127 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode_code_point, encode_code_point, DecodeResult, Error};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// The order in which the octets of a 16-bit word are encoded.
+///
+/// Used with [`encode_u16s`] and [`decode_to_u16s`]. See them for examples.
+///
+/// [`encode_u16s`]: fn.encode_u16s.html
+/// [`decode_to_u16s`]: fn.decode_to_u16s.html
+pub enum Endian {
+    /// The low octet comes first, so it picks the character within a block,
+    /// and the high octet picks the block.
+    Little,
+    /// The high octet comes first, so it picks the character within a block,
+    /// and the low octet picks the block.
+    Big,
+}
+
+impl Endian {
+    #[inline]
+    fn split(self, word: u16) -> [u8; 2] {
+        match self {
+            Endian::Little => word.to_le_bytes(),
+            Endian::Big => word.to_be_bytes(),
+        }
+    }
+
+    #[inline]
+    fn join(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// Encode 16-bit words as base65536, one character per word.
+///
+/// The result is the same as encoding the octets of each word, in the order
+/// given by `endian`, with [`encode`]. As every word fills a character, the
+/// output never ends in a padding character.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{encode, encode_u16s, Endian};
+///
+/// assert_eq!("㘁㠃", encode_u16s(&[0x0201, 0x0403], Endian::Little));
+/// assert_eq!("㘁㠃", encode_u16s(&[0x0102, 0x0304], Endian::Big));
+/// assert_eq!(encode(&[1, 2, 3, 4], None), encode_u16s(&[0x0201, 0x0403], Endian::Little));
+/// ```
+///
+/// [`encode`]: fn.encode.html
+pub fn encode_u16s<T>(input: &T, endian: Endian) -> String
+where
+    T: ?Sized + AsRef<[u16]>,
+{
+    // every word becomes a character of up to four bytes
+    let input = input.as_ref();
+    let mut output = String::with_capacity(input.len() * 4);
+    output.extend(input.iter().map(|&word| {
+        let [byte1, byte2] = endian.split(word);
+        encode_code_point(byte1, Some(byte2))
+    }));
+    output
+}
+
+/// Decode from a reference to a base65536-encoded string as 16-bit words,
+/// reading the octets of each word in the order given by `endian`.
+///
+/// # Errors
+///
+/// If the input string contains a character that isn't part of base65536,
+/// [`Error::InvalidCodePoint`] is returned, along with the bad character and
+/// its offset.
+///
+/// If the input contains a padding character, it encodes an odd number of
+/// octets, which can't be split into words, so [`Error::InvalidLength`] is
+/// returned.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::{decode_to_u16s, Endian, Error};
+///
+/// assert_eq!(vec![0x0201, 0x0403], decode_to_u16s("㘁㠃", Endian::Little)?);
+/// assert_eq!(vec![0x0102, 0x0304], decode_to_u16s("㘁㠃", Endian::Big)?);
+///
+/// // "㘁ᔃ" encodes three octets
+/// assert_eq!(Err(Error::InvalidLength), decode_to_u16s("㘁ᔃ", Endian::Little));
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+/// [`Error::InvalidLength`]: enum.Error.html#variant.InvalidLength
+pub fn decode_to_u16s<T>(input: &T, endian: Endian) -> DecodeResult<Vec<u16>>
+where
+    T: ?Sized + AsRef<str>,
+{
+    let input = input.as_ref();
+    let mut output = Vec::with_capacity(input.len() / 3);
+    for (index, code_point) in input.char_indices() {
+        match decode_code_point(code_point) {
+            Some((byte1, Some(byte2))) => output.push(endian.join([byte1, byte2])),
+            Some((_, None)) => return Err(Error::InvalidLength),
+            None => return Err(Error::InvalidCodePoint(index, code_point)),
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,219 @@
+// Copyright 2017-2019 Emma Welker (nuew)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{decode, decode_code_point, DecodeResult, Error, WrapOptions};
+
+/// Decode from a reference to a base65536-encoded string as octets, requiring
+/// it to be wrapped exactly as [`encode`] would have wrapped it.
+///
+/// Unlike the `ignore_garbage` option of [`decode`], which accepts anything at
+/// all between base65536 characters, this accepts line breaks only where
+/// [`encode`] would have put them given the same options, and only if they
+/// are exactly the configured end-of-line string. Any configured line prefix
+/// must likewise be present at the start of every line, and is stripped.
+///
+/// Input that ends with a line break is rejected, as [`encode`] never
+/// produces it, unless the options were created with
+/// [`WrapOptions::with_trailing_eol`]. Even then, the final line break may be
+/// missing, so that input with trailing whitespace trimmed still decodes.
+///
+/// # Panics
+///
+/// Panics if set to wrap every 0 columns.
+///
+/// # Errors
+///
+/// A missing, misplaced, or malformed line break or prefix is reported as an
+/// [`Error::InvalidCodePoint`] at the first character that doesn't fit the
+/// wrapping, along with its position in the input.
+///
+/// Otherwise, the errors are the same as those of [`decode`].
+///
+/// # Examples
+///
+/// ```rust
+/// # fn test() -> Result<(), Box<std::error::Error>> {
+/// use base65536::{decode_wrapped, Error, WrapOptions};
+///
+/// assert_eq!(b"hello world", &decode_wrapped("驨ꍬ啯\n𒁷ꍲᕤ", 3)?[..]);
+/// assert_eq!(
+///     b"hello world",
+///     &decode_wrapped("驨ꍬ啯\r\n𒁷ꍲᕤ", WrapOptions::WrapAtWith(3, "\r\n"))?[..]
+/// );
+/// assert_eq!(
+///     b"hello world",
+///     &decode_wrapped("> 驨ꍬ啯\n> 𒁷ꍲᕤ", WrapOptions::WrapAt(3).with_prefix("> ")?)?[..]
+/// );
+///
+/// assert_eq!(
+///     Err(Error::InvalidCodePoint(6, '\n')),
+///     decode_wrapped("驨ꍬ\n啯𒁷ꍲᕤ", 3)
+/// );
+/// # Ok(()) }
+/// # test().unwrap();
+/// ```
+///
+/// [`decode`]: fn.decode.html
+/// [`encode`]: fn.encode.html
+/// [`Error::InvalidCodePoint`]: enum.Error.html#variant.InvalidCodePoint
+/// [`WrapOptions::with_trailing_eol`]: enum.WrapOptions.html#method.with_trailing_eol
+pub fn decode_wrapped<'a, T, W>(input: &T, wrap: W) -> DecodeResult<Vec<u8>>
+where
+    T: ?Sized + AsRef<str>,
+    W: Into<WrapOptions<'a>>,
+{
+    let input = input.as_ref();
+    let format = wrap.into().line_format();
+    let columns = format.columns.unwrap_or(usize::MAX);
+    assert!(columns > 0, "cannot wrap every 0 columns");
+
+    let mut buf = Vec::with_capacity(input.len());
+    let mut done = false;
+    let mut pos = 0;
+    while pos < input.len() {
+        let line_start = pos;
+        expect(input, pos, format.prefix)?;
+        pos += format.prefix.len();
+
+        let mut column = 0;
+        while let Some(code_point) = input[pos..].chars().next() {
+            let (byte1, byte2) = match decode_code_point(code_point) {
+                Some(_) if done => return Err(Error::InvalidLength),
+                Some(bytes) => bytes,
+                None => break,
+            };
+
+            let width = format.measure.width(byte2);
+            if column > 0 && column + width > columns {
+                break;
+            }
+
+            buf.push(byte1);
+            if let Some(byte2) = byte2 {
+                buf.push(byte2);
+            }
+            done = byte2.is_none();
+
+            pos += code_point.len_utf8();
+            column += width;
+        }
+
+        let next = match input[pos..].chars().next() {
+            None if column == 0 => {
+                // nothing but a prefix on this line
+                let code_point = input[line_start..].chars().next().unwrap();
+                return Err(Error::InvalidCodePoint(line_start, code_point));
+            }
+            None => break,
+            Some(next) => next,
+        };
+
+        // a full line must be followed by a line break, and nothing else may be
+        if column == 0 || (!format.eol.is_empty() && decode_code_point(next).is_some()) {
+            return Err(Error::InvalidCodePoint(pos, next));
+        }
+
+        let eol_start = pos;
+        expect(input, pos, format.eol)?;
+        pos += format.eol.len();
+
+        if pos == input.len() {
+            if format.trailing_eol {
+                break;
+            } else {
+                return Err(Error::InvalidCodePoint(eol_start, next));
+            }
+        }
+
+        // the line may only have been broken if the next character didn't fit
+        let following = input[pos..]
+            .strip_prefix(format.prefix)
+            .and_then(|rest| rest.chars().next())
+            .and_then(decode_code_point);
+        if let Some((_, byte2)) = following {
+            if column + format.measure.width(byte2) <= columns {
+                return Err(Error::InvalidCodePoint(eol_start, next));
+            }
+        }
+    }
+
+    Ok(buf)
+}
+
+/// Checks that `expected` occurs in `input` at `pos`, returning an error at
+/// the first character that doesn't match.
+fn expect(input: &str, pos: usize, expected: &str) -> DecodeResult<()> {
+    let rest = &input[pos..];
+    if rest.starts_with(expected) {
+        return Ok(());
+    }
+
+    let (index, code_point) = rest
+        .char_indices()
+        .zip(expected.chars())
+        .find(|((_, actual), expected)| actual != expected)
+        .map(|(actual, _)| actual)
+        // the input ended partway through
+        .unwrap_or_else(|| (0, rest.chars().next().unwrap()));
+    Err(Error::InvalidCodePoint(pos + index, code_point))
+}
+
+/// Infers the [`WrapOptions`] used to encode a base65536-encoded string.
+///
+/// The width is taken from the length of the first line, and the end-of-line
+/// string from whatever follows it, which is then checked against the rest
+/// of the input as if with [`decode_wrapped`]. Input containing no line
+/// breaks at all is reported as [`WrapOptions::NoWrap`].
+///
+/// Returns `None` if the input isn't consistently wrapped base65536.
+///
+/// # Examples
+///
+/// ```rust
+/// use base65536::{detect_wrap, WrapOptions};
+///
+/// assert_eq!(Some(WrapOptions::WrapAt(3)), detect_wrap("驨ꍬ啯\n𒁷ꍲᕤ"));
+/// assert_eq!(
+///     Some(WrapOptions::WrapAtWith(2, "\r\n")),
+///     detect_wrap("驨ꍬ\r\n啯𒁷\r\nꍲᕤ")
+/// );
+/// assert_eq!(Some(WrapOptions::NoWrap), detect_wrap("驨ꍬ啯𒁷ꍲᕤ"));
+/// assert_eq!(None, detect_wrap("驨ꍬ啯\n𒁷ꍲ\nᕤ"));
+/// ```
+///
+/// [`WrapOptions`]: enum.WrapOptions.html
+/// [`WrapOptions::NoWrap`]: enum.WrapOptions.html#variant.NoWrap
+/// [`decode_wrapped`]: fn.decode_wrapped.html
+pub fn detect_wrap(input: &str) -> Option<WrapOptions<'_>> {
+    let is_base65536 = |code_point: char| decode_code_point(code_point).is_some();
+
+    let (columns, eol) = match input.find(|c| !is_base65536(c)) {
+        None => {
+            return decode(input, false).ok().map(|_| WrapOptions::NoWrap);
+        }
+        Some(0) => return None,
+        Some(eol_start) => {
+            let rest = &input[eol_start..];
+            let eol_len = rest.find(is_base65536).unwrap_or(rest.len());
+            (input[..eol_start].chars().count(), &rest[..eol_len])
+        }
+    };
+
+    let wrap = if eol == "\n" {
+        WrapOptions::WrapAt(columns)
+    } else {
+        WrapOptions::WrapAtWith(columns, eol)
+    };
+    decode_wrapped(input, wrap).ok().map(|_| wrap)
+}